@@ -0,0 +1,34 @@
+extern crate rusteroids;
+
+use rusteroids::gym;
+use std::time::Instant;
+
+static STEPS: usize = 200000;
+
+// how many gym steps a second the simulation manages on one core -
+// run it with `cargo run --release --example gym_speed`
+fn main() {
+    let mut env = gym::Environment::new(gym::Rewards::new());
+    let mut seed = 0;
+    env.reset(seed);
+
+    let mut action = gym::Action::new();
+    let start = Instant::now();
+    for i in 0..STEPS {
+        action.thrust = i % 90 < 60;
+        action.left = i % 200 < 30;
+        action.fire = i % 10 == 0;
+        action.mine = i % 150 == 0;
+        action.shield = i % 300 < 40;
+
+        let (_, _, done, _) = env.step(&action);
+        if done {
+            seed += 1;
+            env.reset(seed);
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+    println!("{} steps over {} episodes in {:.1}s - {:.0} steps a second", STEPS, seed + 1, seconds, STEPS as f64 / seconds);
+}
@@ -0,0 +1,265 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use actor;
+use faction::Faction;
+use messages::GameInstructions;
+use messages::PlayerInstructions;
+
+static TICKS_PER_SECOND: usize = 60;
+static TOAST_TIME: usize = 240;
+
+// the things that happen in a run that achievements can count
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Token,
+    Asteroid,
+    Ship,
+    Second,
+    Shield,
+    Deflect,
+    Hyperspace,
+    Death
+}
+
+// count `event` up to `target` in a single run,
+// starting again from zero whenever `reset_on` happens
+pub struct Achievement {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub event: Event,
+    pub target: usize,
+    pub reset_on: Option<Event>
+}
+
+static ACHIEVEMENTS: &'static [Achievement] = &[
+    Achievement { key: "first_token",   name: "first token",                        event: Event::Token,      target: 1,   reset_on: None },
+    Achievement { key: "no_shield",     name: "collect 10 tokens without shield",   event: Event::Token,      target: 10,  reset_on: Some(Event::Shield) },
+    Achievement { key: "rock_breaker",  name: "destroy 50 asteroids in one run",    event: Event::Asteroid,   target: 50,  reset_on: None },
+    Achievement { key: "ace",           name: "shoot down 10 ships in one run",     event: Event::Ship,       target: 10,  reset_on: None },
+    Achievement { key: "survivor",      name: "survive 5 minutes",                  event: Event::Second,     target: 300, reset_on: None },
+    Achievement { key: "untouchable",   name: "deflect 20 hits with the shield",    event: Event::Deflect,    target: 20,  reset_on: None },
+    Achievement { key: "jumper",        name: "hyperspace 5 times in one run",      event: Event::Hyperspace, target: 5,   reset_on: None }
+];
+
+pub struct Achievements{
+    progress: Vec<usize>,
+    unlocked: Vec<bool>,
+    toasts: Vec<&'static str>,
+    toast_timer: usize,
+    ticks: usize,
+    alive: bool,
+    new_unlocks: bool
+}
+
+impl Achievements{
+    pub fn new() -> Achievements {
+        Achievements {
+            progress: ACHIEVEMENTS.iter().map(|_| 0).collect(),
+            unlocked: ACHIEVEMENTS.iter().map(|_| false).collect(),
+            toasts: vec!(),
+            toast_timer: 0,
+            ticks: 0,
+            alive: true,
+            new_unlocks: false
+        }
+    }
+
+    // one unlocked key per line - anything unreadable is ignored
+    pub fn load(path: &str) -> Achievements {
+        let mut achievements = Achievements::new();
+        let mut contents = String::new();
+
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            Ok(_)   => (),
+            Err(_)  => return achievements
+        }
+
+        for line in contents.lines() {
+            match ACHIEVEMENTS.iter().position(|a| a.key == line.trim()) {
+                Some(i) => achievements.unlocked[i] = true,
+                None    => ()
+            }
+        }
+
+        achievements
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut f = File::create(path)?;
+        for (a, &unlocked) in ACHIEVEMENTS.iter().zip(self.unlocked.iter()) {
+            if unlocked {
+                write!(f, "{}\n", a.key)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn restart(&mut self){
+        self.progress = ACHIEVEMENTS.iter().map(|_| 0).collect();
+        self.ticks = 0;
+        self.alive = true;
+    }
+
+    // true once after anything new has been unlocked
+    pub fn new_unlocks(&mut self) -> bool {
+        let new_unlocks = self.new_unlocks;
+        self.new_unlocks = false;
+        new_unlocks
+    }
+
+    pub fn tick(&mut self){
+        if self.alive {
+            self.ticks += 1;
+            if self.ticks % TICKS_PER_SECOND == 0 {
+                self.record(Event::Second);
+            }
+        }
+
+        if self.toast_timer > 0 {
+            self.toast_timer -= 1;
+            if self.toast_timer == 0 && !self.toasts.is_empty() {
+                self.toasts.remove(0);
+                if !self.toasts.is_empty() {
+                    self.toast_timer = TOAST_TIME;
+                }
+            }
+        }
+    }
+
+    // the latest unlock and how long it has left on screen
+    pub fn get_toast(&self) -> Option<(&'static str, f32)> {
+        match self.toasts.first() {
+            Some(name) => Some((name, self.toast_timer as f32 / TOAST_TIME as f32)),
+            None       => None
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let count = self.unlocked.iter().filter(|&&u| u).count();
+        format!("achievements {}/{}", count, ACHIEVEMENTS.len())
+    }
+
+    // collisions and the player's own inputs
+    pub fn process_player_messages(&mut self, messages: &Vec<(i32, PlayerInstructions)>){
+        for &(id, ref msg) in messages.iter(){
+            match msg {
+                &PlayerInstructions::ShieldUp if id == 1        => self.record(Event::Shield),
+                &PlayerInstructions::Deflect(_, _, 1, _, _)        => self.record(Event::Deflect),
+                _                                               => ()
+            }
+        }
+    }
+
+    pub fn process_messages(&mut self, messages: &Vec<(GameInstructions, actor::ActorView)>){
+        for &(ref msg, ref v) in messages.iter(){
+            match msg {
+                &GameInstructions::Collect(_) if v.id == 1  => self.record(Event::Token),
+                &GameInstructions::Hyperspace if v.id == 1  => self.record(Event::Hyperspace),
+                &GameInstructions::Explode if v.id == 1     => {
+                    self.record(Event::Death);
+                    self.alive = false;
+                },
+                &GameInstructions::Kill(1)                  => {
+                    if v.faction == Faction::Unaligned {
+                        self.record(Event::Asteroid);
+                    } else {
+                        self.record(Event::Ship);
+                    }
+                },
+                _                                           => ()
+            }
+        }
+    }
+
+    fn record(&mut self, event: Event){
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            if a.reset_on.as_ref() == Some(&event) {
+                self.progress[i] = 0;
+            }
+
+            if a.event != event || self.unlocked[i] {
+                continue;
+            }
+
+            self.progress[i] += 1;
+            if self.progress[i] >= a.target {
+                self.unlock(i);
+            }
+        }
+    }
+
+    fn unlock(&mut self, i: usize){
+        self.unlocked[i] = true;
+        self.new_unlocks = true;
+        self.toasts.push(ACHIEVEMENTS[i].name);
+        if self.toasts.len() == 1 {
+            self.toast_timer = TOAST_TIME;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    fn unlocked(achievements: &Achievements, key: &str) -> bool {
+        achievements.unlocked[ACHIEVEMENTS.iter().position(|a| a.key == key).unwrap()]
+    }
+
+    #[test]
+    fn an_achievement_unlocks_when_its_count_is_reached(){
+        let mut achievements = Achievements::new();
+        for _ in 0..4 {
+            achievements.record(Event::Hyperspace);
+        }
+        assert!(!unlocked(&achievements, "jumper"));
+        assert!(!achievements.new_unlocks());
+
+        achievements.record(Event::Hyperspace);
+        assert!(unlocked(&achievements, "jumper"));
+        assert!(achievements.new_unlocks());
+        assert!(!achievements.new_unlocks());
+        assert_eq!(achievements.get_toast(), Some(("hyperspace 5 times in one run", 1.0)));
+    }
+
+    #[test]
+    fn raising_the_shield_starts_the_count_again(){
+        let mut achievements = Achievements::new();
+        for _ in 0..9 {
+            achievements.record(Event::Token);
+        }
+        achievements.record(Event::Shield);
+        for _ in 0..9 {
+            achievements.record(Event::Token);
+        }
+        assert!(unlocked(&achievements, "first_token"));
+        assert!(!unlocked(&achievements, "no_shield"));
+
+        achievements.record(Event::Token);
+        assert!(unlocked(&achievements, "no_shield"));
+    }
+
+    #[test]
+    fn unlocks_survive_a_save_and_load_but_progress_doesnt(){
+        let path = env::temp_dir().join(format!("rusteroids-{}-unlocks_survive_a_save_and_load.achievements", process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut achievements = Achievements::new();
+        achievements.record(Event::Token);
+        achievements.record(Event::Asteroid);
+        achievements.save(path).unwrap();
+
+        let loaded = Achievements::load(path);
+        assert!(unlocked(&loaded, "first_token"));
+        assert!(!unlocked(&loaded, "rock_breaker"));
+        assert_eq!(loaded.progress, Achievements::new().progress);
+        assert_eq!(loaded.summary(), format!("achievements 1/{}", ACHIEVEMENTS.len()));
+        assert_eq!(Achievements::load("no such file").summary(), format!("achievements 0/{}", ACHIEVEMENTS.len()));
+        fs::remove_file(path).unwrap();
+    }
+}
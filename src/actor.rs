@@ -2,20 +2,33 @@ use std::fmt::Debug;
 use std::cmp::PartialEq;
 use messages::PlayerInstructions;
 use messages::GameInstructions;
+use faction::Faction;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum CollisionType{
     Collide,
     Collect,
+    Shield,
+    Pickup,
     Ignore
 }
 
+impl CollisionType {
+    // anything that can be hit - shields included
+    pub fn is_solid(&self) -> bool {
+        *self == CollisionType::Collide || *self == CollisionType::Shield
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ActorView{
     pub id: i32,
     pub parent: i32,
+    pub faction: Faction,
     pub x: f32,
     pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
     pub width: f32,
     pub height: f32,
     pub rotation: f32,
@@ -25,10 +38,22 @@ pub struct ActorView{
     pub show_secondary: bool,
     pub secondary_shape: Option<Vec<f32>>,
     pub secondary_color: Option<Vec<f32>>,
-    pub meter: f32
+    pub meter: f32,
+    pub cooldown: f32,
+    pub damage: f32,
+    pub hull: f32
 }
 
 
+// what `other` does to `view` when they hit - faster impacts hurt more
+pub fn impact_damage(view: &ActorView, other: &ActorView) -> f32 {
+    let dvx = other.vx - view.vx;
+    let dvy = other.vy - view.vy;
+    let speed = (dvx * dvx + dvy * dvy).sqrt();
+    let scale = (0.5 + speed / 40.0).min(3.0);
+    other.damage * scale
+}
+
 pub trait Actor : Debug + PartialEq + Clone {
     fn update(&mut self, output_messages: &mut Vec<(GameInstructions, ActorView)>);
     fn get_view(&self) -> ActorView;
@@ -36,4 +61,7 @@ pub trait Actor : Debug + PartialEq + Clone {
     fn kill(&mut self);
     fn get_id(&self)->i32;
     fn is_alive(&self)->bool;
+
+    // an outside push, e.g. from a tether - most things ignore it
+    fn apply_impulse(&mut self, _dvx: f32, _dvy: f32){}
 }
@@ -1,26 +1,72 @@
 use actor;
+use actor::Actor;
 use spaceship;
 use bullet;
 use asteroid;
 use kamikaze;
 use explosion;
 use token;
+use mine;
+use mothership;
+use pickup;
+use faction::Faction;
+use director::SpawnKind;
+use loot::LootTable;
 use spaceship_agent;
+use agent;
+use agent::Agent;
+use squadron;
 use messages::PlayerInstructions;
 use messages::GameInstructions;
 use rand;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::mem;
+use dice::Dice;
+
+static BLAST_DAMAGE: f32 = 60.0;
+static TRACTOR_RANGE: f32 = 800.0;
+static TETHER_LENGTH: f32 = 250.0;
+static TETHER_BREAK: f32 = 1500.0;
+static TETHER_SPRING: f32 = 0.02;
+static TETHER_DAMPING: f32 = 0.1;
+static TETHER_SHIP_SHARE: f32 = 0.2;
+static FLING_SPEED: f32 = 60.0;
+static HYPERSPACE_CLEARANCE: f32 = 500.0;
+static HYPERSPACE_ATTEMPTS: i32 = 30;
+
 //use std::num::Float;
 //use std::num::FloatMath;
 
-#[derive(Clone, Debug, PartialEq)]
 pub struct ActorManager{
     spaceships: Vec<spaceship::Spaceship>,
+    minelayers: Vec<spaceship::Spaceship>,
     bullets: Vec<bullet::Bullet>,
     asteroids: Vec<asteroid::Asteroid>,
     kamikaze: Vec<kamikaze::Kamikaze>,
     explosions: Vec<explosion::Explosion>,
     tokens: Vec<token::Token>,
+    mines: Vec<mine::Mine>,
+    bosses: Vec<mothership::Mothership>,
+    boss_parts: Vec<mothership::MothershipPart>,
+    pickups: Vec<pickup::Pickup>,
+    agents: Vec<Box<dyn Agent>>,
+    squadrons: Vec<squadron::Squadron>,
+    tethers: Vec<(i32, i32)>,
+    pending: Vec<(i32, PlayerInstructions)>,
+    player_input: Vec<PlayerInstructions>,
+    course: Option<Vec<(i32, i32)>>,
+    player_stats: spaceship::ShipStats,
+    drag: f32,
+    seed: Option<u64>,
+    // everything random about the world rolls these, so a seed replays it
+    rng: Dice,
+    avoidance: bool,
+    skill: spaceship_agent::Skill,
+    trees: spaceship_agent::Trees,
+    ai_stats: spaceship::ShipStats,
+    tokens_placed: u64,
     count:i32,
     px: f32,
     py: f32
@@ -30,11 +76,31 @@ impl ActorManager {
     pub fn new() -> ActorManager {
         ActorManager {
             spaceships : vec!(),
+            minelayers: vec!(),
             bullets: vec!(),
             asteroids: vec!(),
             kamikaze: vec!(),
             explosions: vec!(),
             tokens: vec!(),
+            mines: vec!(),
+            bosses: vec!(),
+            boss_parts: vec!(),
+            pickups: vec!(),
+            agents: vec!(),
+            squadrons: vec!(),
+            tethers: vec!(),
+            pending: vec!(),
+            player_input: vec!(),
+            course: None,
+            player_stats: spaceship::ShipStats::new(),
+            drag: spaceship::DRAG,
+            seed: None,
+            rng: Dice::from_rng(&mut rand::thread_rng()),
+            avoidance: true,
+            skill: spaceship_agent::Skill::new(),
+            trees: spaceship_agent::Trees::new(),
+            ai_stats: spaceship::ShipStats::new(),
+            tokens_placed: 0,
             count: 1,
             px: 0.0,
             py: 0.0
@@ -42,23 +108,58 @@ impl ActorManager {
     }
 
     pub fn get(&self) -> Vec<actor::ActorView> {
-        let mut all_views = ActorManager::get_views(&self.spaceships.clone());
-        all_views.extend(ActorManager::get_views(&self.bullets.clone()));
-        all_views.extend(ActorManager::get_views(&self.asteroids.clone()));
-        all_views.extend(ActorManager::get_views(&self.kamikaze.clone()));
-        all_views.extend(ActorManager::get_views(&self.explosions.clone()));
-        all_views.extend(ActorManager::get_views(&self.tokens.clone()));
+        let mut all_views = ActorManager::get_views(&self.spaceships);
+        all_views.extend(ActorManager::get_views(&self.minelayers));
+        all_views.extend(ActorManager::get_views(&self.bullets));
+        all_views.extend(ActorManager::get_views(&self.asteroids));
+        all_views.extend(ActorManager::get_views(&self.kamikaze));
+        all_views.extend(ActorManager::get_views(&self.explosions));
+        all_views.extend(ActorManager::get_views(&self.tokens));
+        all_views.extend(ActorManager::get_views(&self.mines));
+        all_views.extend(ActorManager::get_views(&self.bosses));
+        all_views.extend(ActorManager::get_views(&self.boss_parts));
+        all_views.extend(ActorManager::get_views(&self.pickups));
         all_views
     }
 
     pub fn get_collectables(&self) -> Vec<actor::ActorView> {
-        ActorManager::get_views(&self.tokens.clone())
+        ActorManager::get_views(&self.tokens)
+    }
+
+    pub fn get_bosses(&self) -> Vec<actor::ActorView> {
+        ActorManager::get_views(&self.bosses)
+    }
+
+    pub fn get_rivals(&self) -> Vec<actor::ActorView> {
+        self.spaceships.iter().filter(|s| ActorManager::is_rival(s)).map(|s| s.get_view()).collect()
+    }
+
+    pub fn get_player(&self) -> Option<actor::ActorView> {
+        self.spaceships.iter().find(|s| s.get_id() == 1).map(|s| s.get_view())
+    }
+
+    // how many of a kind are out there, without building every view
+    pub fn count(&self, kind: &SpawnKind) -> usize {
+        match *kind {
+            SpawnKind::Asteroid     => self.asteroids.iter().filter(|a| !a.is_debris()).count(),
+            SpawnKind::Spaceship    => self.spaceships.iter().filter(|s| s.get_id() != 1 && !self.in_squadron(s.get_id()) && !ActorManager::is_rival(s)).count(),
+            SpawnKind::Kamikaze     => self.kamikaze.len(),
+            SpawnKind::Minelayer    => self.minelayers.len(),
+            SpawnKind::Squadron     => self.squadrons.len(),
+            SpawnKind::Rival        => self.spaceships.iter().filter(|s| ActorManager::is_rival(s)).count()
+        }
     }
 
     pub fn update(&mut self, messages:Vec<(i32, PlayerInstructions)>, output_messages:&mut Vec<(GameInstructions, actor::ActorView)>){
-        let mut player_messages = messages;//messages.clone();
+        // keyboard input only reaches the ship if a Keyboard agent is flying it
+        let (input, mut player_messages): (Vec<_>, Vec<_>) = messages.into_iter().partition(|&(id, ref m)| id == 1 && m.is_control());
+        let input: Vec<PlayerInstructions> = input.into_iter().map(|(_, m)| m).collect();
+        player_messages.extend(mem::replace(&mut self.pending, vec!()).into_iter());
 
-        for ref actor in self.get().iter(){
+        // everything as it stands at the start of the tick - built once, not per ship
+        let views = self.get();
+
+        for ref actor in views.iter(){
             if actor.id == 1 {
                 self.px = actor.x;
                 self.py = actor.y;
@@ -67,21 +168,115 @@ impl ActorManager {
         }
 
 
-        for ship in ActorManager::get_views(&self.spaceships.clone()).iter(){
-            if ship.id == 1 {
-                // forget about the player
-                continue;
+        let mut ships = ActorManager::get_views(&self.spaceships);
+        ships.extend(ActorManager::get_views(&self.minelayers).into_iter());
+        let surroundings: Vec<(actor::ActorView, Vec<actor::ActorView>)> = ships.into_iter()
+            .map(|ship| { let nearest = ActorManager::get_nearest(&views, &ship, self.skill.detection_range); (ship, nearest) })
+            .collect();
+
+        // agents outlive a tick, so drop the ones whose ships are gone
+        self.agents.retain(|a| surroundings.iter().any(|&(ref ship, _)| ship.id == a.get_id()));
+
+        let ship_views: Vec<actor::ActorView> = surroundings.iter().map(|&(ref ship, _)| ship.clone()).collect();
+        let mut orders = vec!();
+        for squad in self.squadrons.iter_mut(){
+            orders.extend(squad.update(&ship_views, self.skill.detection_range).into_iter());
+        }
+        self.squadrons.retain(|s| !s.members.is_empty());
+
+        let collectables = self.get_collectables();
+        self.player_input = vec!();
+        for agent in self.agents.iter_mut(){
+            let id = agent.get_id();
+            let order = orders.iter().find(|&&(o, _)| o == id).map(|&(_, ref order)| order);
+            match surroundings.iter().find(|&&(ref ship, _)| ship.id == id) {
+                Some(&(ref ship, ref nearest)) => {
+                    let around = agent::Surroundings {
+                        nearby: nearest,
+                        collectables: &collectables,
+                        input: &input,
+                        order: order,
+                        avoidance: self.avoidance
+                    };
+                    let before = player_messages.len();
+                    agent.control(ship, &around, &mut player_messages);
+                    if id == 1 {
+                        self.player_input = player_messages[before..].iter().map(|&(_, ref m)| m.clone()).collect();
+                    }
+                },
+                None => ()
             }
-            let nearest = self.get_nearest(ship);
-            spaceship_agent::set_instructions(ship.clone(), nearest, &mut player_messages);
+        }
+
+        self.trigger_mines(&views, &mut player_messages);
+
+        let hits = ActorManager::get_hits(&player_messages);
+
+        for boss in self.bosses.iter_mut(){
+            boss.track((self.px, self.py));
+        }
+
+        for k in self.kamikaze.iter_mut(){
+            k.track((self.px, self.py));
         }
 
         self.spaceships = ActorManager::update_actor_list(self.px, self.py, &mut self.spaceships, &player_messages, output_messages);
+        self.minelayers = ActorManager::update_actor_list(self.px, self.py, &mut self.minelayers, &player_messages, output_messages);
         self.bullets    = ActorManager::update_actor_list(self.px, self.py, &mut self.bullets, &player_messages, output_messages);
         self.asteroids  = ActorManager::update_actor_list(self.px, self.py, &mut self.asteroids, &player_messages, output_messages);
         self.kamikaze  = ActorManager::update_actor_list(self.px, self.py, &mut self.kamikaze, &player_messages, output_messages);
         self.explosions  = ActorManager::update_actor_list(self.px, self.py, &mut self.explosions, &player_messages, output_messages);
         self.tokens  = ActorManager::update_actor_list(self.px, self.py, &mut self.tokens, &player_messages, output_messages);
+        self.mines  = ActorManager::update_actor_list(self.px, self.py, &mut self.mines, &player_messages, output_messages);
+        self.bosses  = ActorManager::update_actor_list(self.px, self.py, &mut self.bosses, &player_messages, output_messages);
+        self.attach_boss_parts(output_messages);
+        self.boss_parts  = ActorManager::update_actor_list(self.px, self.py, &mut self.boss_parts, &player_messages, output_messages);
+        self.pickups  = ActorManager::update_actor_list(self.px, self.py, &mut self.pickups, &player_messages, output_messages);
+
+        self.apply_tethers();
+
+        ActorManager::credit_kills(&hits, output_messages);
+    }
+
+    fn get_hits(messages:&Vec<(i32, PlayerInstructions)>) -> Vec<(i32, i32)>{
+        let mut hits = vec!();
+        for &(id, ref message) in messages.iter(){
+            match message {
+                &PlayerInstructions::Impact(_, _, owner) => hits.push((id, owner)),
+                _                                        => ()
+            }
+        }
+        hits
+    }
+
+    // anything that blew up this tick after being hit is a kill for whoever hit it -
+    // shots always count, but ramming only does if the rammer lives through it
+    fn credit_kills(hits:&Vec<(i32, i32)>, output_messages:&mut Vec<(GameInstructions, actor::ActorView)>){
+        let mut kills = vec!();
+        let destroyed: Vec<i32> = output_messages.iter().filter_map(|&(ref msg, ref v)| match msg {
+            &GameInstructions::Explode | &GameInstructions::Blast | &GameInstructions::Detonate => Some(v.id),
+            _ => None
+        }).collect();
+
+        for &(ref msg, ref v) in output_messages.iter(){
+            match msg {
+                &GameInstructions::Explode | &GameInstructions::Blast | &GameInstructions::Detonate => {
+                    match hits.iter().find(|&&(id, owner)| id == v.id && owner != v.id) {
+                        Some(&(_, owner))   => {
+                            // a collision hits both sides - a shot doesn't hit back
+                            let rammed = hits.iter().any(|&(id, by)| id == owner && (by == v.id || (v.parent != 0 && by == v.parent)));
+                            if !(rammed && destroyed.contains(&owner)) {
+                                kills.push((GameInstructions::Kill(owner), v.clone()));
+                            }
+                        },
+                        None                => ()
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        output_messages.extend(kills.into_iter());
     }
 
     pub fn process_messages(&mut self, output_messages: &Vec<(GameInstructions, actor::ActorView)>){
@@ -89,15 +284,23 @@ impl ActorManager {
         for &(ref msg, ref v) in output_messages.iter(){
             //println!("{} : {}", msg, v);
             match msg{
-                &GameInstructions::Fire          => self.add_bullet(v.id, v.x as i32, v.y as i32, v.rotation * 180.0 / 3.14159265359),
+                &GameInstructions::Fire          => self.add_bullet(v.id, v.x as i32, v.y as i32, v.rotation * 180.0 / 3.14159265359, v.faction.clone()),
                 &GameInstructions::Explode       => self.add_explosion(v.x as i32, v.y as i32, (v.width + v.height) as i32 / 2, v.rotation),
                 &GameInstructions::Trail         => self.add_explosion(v.x as i32 - v.height as i32 * 2 * v.rotation.sin() as i32, v.y as i32 - v.height as i32 * 2 * v.rotation.cos() as i32, 10, v.rotation),
-                &GameInstructions::NewAsteroid  => self.split_asteroid(v),
-                &GameInstructions::Collect       => {
-                    if v.id == 1{
-                        self.new_token();
-                    }
-                }
+                &GameInstructions::NewAsteroid(ref material) => self.split_asteroid(v, material.clone()),
+                &GameInstructions::Collect(_)   => self.new_token(),
+                &GameInstructions::Hyperspace    => self.hyperspace(v),
+                &GameInstructions::DropMine      => self.add_mine(v.id, v.x as i32, v.y as i32, v.faction.clone()),
+                &GameInstructions::Detonate      => self.blast(v, mine::BLAST_RADIUS, v.parent),
+                &GameInstructions::Blast         => self.blast(v, (v.width + v.height) * 2.0, 0),
+                &GameInstructions::Kill(_)
+                | &GameInstructions::Hit         => (),
+                &GameInstructions::Launch        => self.launch_kamikaze(v),
+                &GameInstructions::PartDestroyed => self.pending.push((v.parent, PlayerInstructions::PartDestroyed)),
+                &GameInstructions::EngineDestroyed => self.pending.push((v.parent, PlayerInstructions::EngineDestroyed)),
+                &GameInstructions::DropLoot(ref table) => self.drop_loot(v, table),
+                &GameInstructions::Loot(ref loot, collector) => self.pending.push((collector, PlayerInstructions::Loot(loot.clone()))),
+                &GameInstructions::Tractor       => self.tractor(v)
             }
         }
 
@@ -105,93 +308,514 @@ impl ActorManager {
 
     pub fn new_player(&mut self){
         let mut p = spaceship::Spaceship::new(1, 0, 0, 0.0);
-        p.set_color(vec!(0.7, 0.7, 0.77));
+        p.set_faction(Faction::Player);
+        p.set_stats(&self.player_stats);
+        p.set_drag(self.drag);
+        p.set_dice(Dice::from_rng(&mut self.rng));
         self.spaceships.push(p);
+        self.assign(Box::new(agent::Keyboard::new(1)));
+    }
+
+    // put an agent in charge of its ship, taking over from whoever had it
+    pub fn assign(&mut self, agent: Box<dyn Agent>){
+        let id = agent.get_id();
+        self.agents.retain(|a| a.get_id() != id);
+        self.agents.push(agent);
+    }
+
+    pub fn get_agent_kind(&self, id: i32) -> Option<agent::Kind> {
+        self.agents.iter().find(|a| a.get_id() == id).map(|a| a.get_kind())
+    }
+
+    // what the player's ship was told to do last tick, whoever was flying it
+    pub fn get_player_input(&self) -> &Vec<PlayerInstructions> {
+        &self.player_input
     }
 
     pub fn new_token(&mut self){
         self.count += 1;
         let id = self.count;
-        let x = rand::thread_rng().gen_range(-10000i32, 10000);
-        let y = rand::thread_rng().gen_range(-10000i32, 10000);
-        self.tokens = vec!(token::Token::new(id, x, y));
+
+        let (x, y) = match self.course {
+            Some(ref mut checkpoints) => {
+                if checkpoints.is_empty() {
+                    // end of the course
+                    self.tokens = vec!();
+                    return;
+                }
+                checkpoints.remove(0)
+            },
+            None => match self.seed {
+                Some(seed) => {
+                    // the nth token lands in the same place for everyone on this seed
+                    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(self.tokens_placed));
+                    (rng.gen_range(-10000i32, 10000), rng.gen_range(-10000i32, 10000))
+                },
+                None => (self.rng.gen_range(-10000i32, 10000), self.rng.gen_range(-10000i32, 10000))
+            }
+        };
+        self.tokens_placed += 1;
+
+        self.tokens = vec!(token::Token::new(id, x, y, &mut self.rng));
+    }
+
+    // lay tokens along a fixed course instead of at random
+    pub fn set_course(&mut self, checkpoints: Vec<(i32, i32)>){
+        self.course = Some(checkpoints);
+        self.new_token();
+    }
+
+    // the checkpoint after the current token, if there is one
+    pub fn get_next_checkpoint(&self) -> Option<(i32, i32)> {
+        match self.course {
+            Some(ref checkpoints) => checkpoints.first().cloned(),
+            None                  => None
+        }
+    }
+
+    pub fn set_player_stats(&mut self, stats: spaceship::ShipStats){
+        self.player_stats = stats;
+    }
+
+    pub fn set_drag(&mut self, drag: f32){
+        self.drag = drag;
+    }
+
+    // place tokens and build the world from a fixed seed rather than at random
+    pub fn set_seed(&mut self, seed: Option<u64>){
+        self.seed = seed;
+        self.rng = match seed {
+            Some(seed) => Dice::new(seed),
+            None       => Dice::from_rng(&mut rand::thread_rng())
+        };
+    }
+
+    // lets AI ships steer round what they're about to hit
+    pub fn set_avoidance(&mut self, avoidance: bool){
+        self.avoidance = avoidance;
+    }
+
+    // how well ships spawned from now on fly and shoot
+    pub fn set_skill(&mut self, skill: spaceship_agent::Skill){
+        self.skill = skill;
+    }
+
+    // how ships spawned from now on decide what to do
+    pub fn set_trees(&mut self, trees: spaceship_agent::Trees){
+        self.trees = trees;
+    }
+
+    pub fn set_ai_stats(&mut self, stats: spaceship::ShipStats){
+        self.ai_stats = stats;
+    }
+
+    // ships that stray more than `radius` from (cx, cy) come back in on the far side
+    pub fn wrap_ships(&mut self, (cx, cy): (f32, f32), radius: f32){
+        for s in self.spaceships.iter_mut(){
+            let v = s.get_view();
+            let (dx, dy) = (v.x - cx, v.y - cy);
+            if dx * dx + dy * dy > radius * radius {
+                s.teleport(cx - dx, cy - dy);
+            }
+        }
     }
 
     pub fn restart(&mut self){
         self.spaceships = vec!();
+        self.minelayers = vec!();
         self.bullets = vec!();
         self.asteroids = vec!();
         self.kamikaze = vec!();
         self.explosions = vec!();
+        self.mines = vec!();
+        self.bosses = vec!();
+        self.boss_parts = vec!();
+        self.pickups = vec!();
+        self.agents = vec!();
+        self.squadrons = vec!();
+        self.tethers = vec!();
+        self.pending = vec!();
+        self.course = None;
+        self.tokens_placed = 0;
+        match self.seed {
+            Some(seed) => self.rng = Dice::new(seed),
+            None       => ()
+        }
         self.new_player();
         self.new_token();
     }
 
-    pub fn new_spaceship(&mut self, x: i32, y:i32){
+    pub fn new_spaceship(&mut self, x: i32, y:i32, faction: Faction){
+        let r = self.rng.gen_range(0.0f32, 360.0);
+        let archetype = if faction == Faction::Traders { spaceship_agent::Archetype::Trader } else { spaceship_agent::Archetype::Fighter };
+        let ship = self.spawn_ai_ship(x, y, r, faction, archetype);
+        self.spaceships.push(ship);
+    }
+
+    // `size` ships in a V around (x, y), all pointing the same way - the first one leads
+    pub fn new_squadron(&mut self, x: i32, y:i32, faction: Faction, size: usize){
+        let r = self.rng.gen_range(0.0f32, 360.0);
+        let mut members = vec!();
+
+        for i in 0..size {
+            let (sx, sy) = squadron::Squadron::slot(i, (x as f32, y as f32), r * 3.14159265359 / 180.0);
+            let ship = self.spawn_ai_ship(sx as i32, sy as i32, r, faction.clone(), spaceship_agent::Archetype::Wingman);
+            members.push(ship.get_id());
+            self.spaceships.push(ship);
+        }
+
+        self.squadrons.push(squadron::Squadron::new(members, faction, Dice::from_rng(&mut self.rng)));
+    }
+
+    // hunts the same token as the player
+    pub fn new_rival(&mut self, x: i32, y:i32){
+        let r = self.rng.gen_range(0.0f32, 360.0);
+        let ship = self.spawn_ai_ship(x, y, r, Faction::Rivals, spaceship_agent::Archetype::Rival);
+        self.spaceships.push(ship);
+    }
+
+    fn is_rival(ship: &spaceship::Spaceship) -> bool {
+        *ship.get_faction() == Faction::Rivals
+    }
+
+    fn in_squadron(&self, id: i32) -> bool {
+        self.squadrons.iter().any(|s| s.members.contains(&id))
+    }
+
+    pub fn new_minelayer(&mut self, x: i32, y:i32){
+        let r = self.rng.gen_range(0.0f32, 360.0);
+        let mut ship = self.spawn_ai_ship(x, y, r, Faction::Pirates, spaceship_agent::Archetype::Minelayer);
+        ship.set_color(vec!(0.6, 0.4, 0.1));
+        self.minelayers.push(ship);
+    }
+
+    // an AI ship and the pilot to fly it - the caller decides which list it goes in
+    fn spawn_ai_ship(&mut self, x: i32, y: i32, r: f32, faction: Faction, archetype: spaceship_agent::Archetype) -> spaceship::Spaceship {
         self.count += 1;
         let id = self.count;
-        let r = rand::thread_rng().gen_range(0.0f32, 360.0);
-        let ship = spaceship::Spaceship::new(id, x, y, r);
-        self.spaceships.push(ship);
+        let mut ship = spaceship::Spaceship::new(id, x, y, r);
+        ship.set_faction(faction);
+        ship.set_drag(self.drag);
+        ship.set_dice(Dice::from_rng(&mut self.rng));
+        ship.set_stats(&self.ai_stats);
+        self.agents.push(Box::new(spaceship_agent::Pilot::new(id, self.trees.get(&archetype), self.skill.clone(), Dice::from_rng(&mut self.rng))));
+        ship
     }
 
-    pub fn new_asteroid(&mut self, x: i32, y:i32){
+    pub fn new_mothership(&mut self, x: i32, y:i32){
         self.count += 1;
         let id = self.count;
-        let ast = asteroid::Asteroid::new(id, x, y);
-        self.asteroids.push(ast);
+        let r = self.rng.gen_range(0.0f32, 360.0);
+        let boss = mothership::Mothership::new(id, x, y, r, 2);
+        self.bosses.push(boss);
+
+        let parts = vec!(
+            (mothership::PartKind::Turret, -200.0, 150.0),
+            (mothership::PartKind::Turret, 200.0, 150.0),
+            (mothership::PartKind::Engine, -180.0, -420.0),
+            (mothership::PartKind::Engine, 180.0, -420.0)
+        );
+
+        for (kind, ox, oy) in parts.into_iter(){
+            self.count += 1;
+            let part_id = self.count;
+            self.boss_parts.push(mothership::MothershipPart::new(part_id, id, kind, ox, oy));
+        }
+
+        self.attach_boss_parts(&mut vec!());
     }
 
-    fn split_asteroid(&mut self, original: &actor::ActorView){
+    pub fn new_asteroid(&mut self, x: i32, y:i32, material: asteroid::Material){
         self.count += 1;
         let id = self.count;
-        let d = original.width / 2.0;
-        let ast = asteroid::Asteroid::new_with_d(id, original.x as i32, original.y as i32, d, 0);
+        let ast = asteroid::Asteroid::new(id, x, y, material, &mut self.rng);
         self.asteroids.push(ast);
+    }
+
+    fn split_asteroid(&mut self, fragment: &actor::ActorView, material: asteroid::Material){
         self.count += 1;
-        let id2 = self.count;
-        let ast = asteroid::Asteroid::new_with_d(id2, original.x as i32, original.y as i32, d, id);
+        let id = self.count;
+        let ast = asteroid::Asteroid::new_fragment(id, fragment.x as i32, fragment.y as i32,
+                                                   fragment.width, fragment.parent, (fragment.vx, fragment.vy), material, &mut self.rng);
         self.asteroids.push(ast);
     }
 
     pub fn new_kamikaze(&mut self, x: i32, y:i32, target:(f32, f32)){
         self.count += 1;
         let id = self.count;
-        let kam = kamikaze::Kamikaze::new(id, x, y, target);
+        let kam = kamikaze::Kamikaze::new(id, x, y, target, Dice::from_rng(&mut self.rng));
         self.kamikaze.push(kam);
     }
 
-    fn add_bullet(&mut self, parent:i32, x:i32, y:i32, r:f32){
+    fn add_bullet(&mut self, parent:i32, x:i32, y:i32, r:f32, faction: Faction){
         self.count += 1;
         let id = self.count;
-        let bullet = bullet::Bullet::new(id, parent, x, y, r);
+        let bullet = bullet::Bullet::new(id, parent, x, y, r, faction);
         self.bullets.push(bullet);
     }
 
-    fn add_explosion(&mut self, x:i32, y:i32, d:i32, r:f32){
-        let expl = explosion::Explosion::new(x, y,  d, r);
-        self.explosions.push(expl);
+    // every ship and what it's towing
+    pub fn get_tethers(&self) -> Vec<(actor::ActorView, actor::ActorView)> {
+        let views = self.get();
+        self.tethers.iter().filter_map(|&(ship, target)| {
+            match (views.iter().find(|v| v.id == ship), views.iter().find(|v| v.id == target)) {
+                (Some(s), Some(t)) => Some((s.clone(), t.clone())),
+                _                  => None
+            }
+        }).collect()
     }
 
-    fn get_nearest(&self, actor: &actor::ActorView) -> Vec<actor::ActorView>{
-        let mut nearest = vec!();
+    // lock on to the nearest rock or pickup, or let go and fling it
+    fn tractor(&mut self, ship: &actor::ActorView){
+        match self.tethers.iter().position(|&(s, _)| s == ship.id) {
+            Some(i) => {
+                let (_, target) = self.tethers.remove(i);
+                let (dx, dy) = (ship.rotation.sin() * FLING_SPEED, ship.rotation.cos() * FLING_SPEED);
+                self.impulse(target, dx, dy);
+                self.pending.push((target, PlayerInstructions::Release));
+                return;
+            },
+            None => ()
+        }
+
+        let mut candidates = ActorManager::get_views(&self.asteroids);
+        candidates.extend(ActorManager::get_views(&self.pickups).into_iter());
+
+        let max_distance = TRACTOR_RANGE * TRACTOR_RANGE;
+        let mut nearest: Option<(i32, f32)> = None;
 
-        for enemy in self.get().iter(){
-            if enemy.id == actor.id {
+        for c in candidates.iter(){
+            if c.collision_type == actor::CollisionType::Ignore
+                || self.tethers.iter().any(|&(_, t)| t == c.id) {
                 continue;
             }
 
-            let max_distance = 2000.0;
-            let dx = enemy.x - actor.x;
-            let dy = enemy.y - actor.y;
+            let dx = c.x - ship.x;
+            let dy = c.y - ship.y;
+            let distance = dx * dx + dy * dy;
+
+            if distance < max_distance && nearest.map_or(true, |(_, d)| distance < d) {
+                nearest = Some((c.id, distance));
+            }
+        }
+
+        match nearest {
+            Some((id, _)) => {
+                self.tethers.push((ship.id, id));
+                self.pending.push((id, PlayerInstructions::Tether(ship.id)));
+            },
+            None => ()
+        }
+    }
+
+    // a damped spring between each ship and its load - pulls the load along
+    // and drags a little on the ship
+    fn apply_tethers(&mut self){
+        let views = self.get();
+        let mut impulses = vec!();
+        let mut released = vec!();
+
+        self.tethers.retain(|&(ship, target)| {
+            let (s, t) = match (views.iter().find(|v| v.id == ship), views.iter().find(|v| v.id == target)) {
+                (Some(s), Some(t)) => (s, t),
+                _                  => {
+                    released.push(target);
+                    return false;
+                }
+            };
+
+            let dx = t.x - s.x;
+            let dy = t.y - s.y;
             let distance = (dx * dx + dy * dy).sqrt();
-            if distance < max_distance{
-                nearest.push(enemy.clone());
+            if distance > TETHER_BREAK || distance == 0.0 {
+                released.push(target);
+                return false;
             }
+
+            let (nx, ny) = (dx / distance, dy / distance);
+            let stretch = distance - TETHER_LENGTH;
+            let ax = -nx * stretch * TETHER_SPRING - (t.vx - s.vx) * TETHER_DAMPING;
+            let ay = -ny * stretch * TETHER_SPRING - (t.vy - s.vy) * TETHER_DAMPING;
+
+            impulses.push((target, ax, ay));
+            impulses.push((ship, -ax * TETHER_SHIP_SHARE, -ay * TETHER_SHIP_SHARE));
+            true
+        });
+
+        for (id, dvx, dvy) in impulses.into_iter(){
+            self.impulse(id, dvx, dvy);
+        }
+
+        for id in released.into_iter(){
+            self.pending.push((id, PlayerInstructions::Release));
+        }
+    }
+
+    fn impulse(&mut self, id: i32, dvx: f32, dvy: f32){
+        for s in self.spaceships.iter_mut().filter(|s| s.get_id() == id) {
+            s.apply_impulse(dvx, dvy);
         }
+        for a in self.asteroids.iter_mut().filter(|a| a.get_id() == id) {
+            a.apply_impulse(dvx, dvy);
+        }
+        for p in self.pickups.iter_mut().filter(|p| p.get_id() == id) {
+            p.apply_impulse(dvx, dvy);
+        }
+    }
+
+    // scatter whatever the table rolls, carried along by the wreck's momentum
+    fn drop_loot(&mut self, wreck: &actor::ActorView, table: &LootTable){
+        for loot in table.roll(&mut self.rng).into_iter(){
+            self.count += 1;
+            let id = self.count;
+            let angle = self.rng.gen_range(0.0f32, 6.283);
+            let speed = self.rng.gen_range(2.0f32, 8.0);
+            let velocity = (wreck.vx * 0.5 + angle.sin() * speed, wreck.vy * 0.5 + angle.cos() * speed);
+            self.pickups.push(pickup::Pickup::new(id, wreck.x as i32, wreck.y as i32, velocity, loot));
+        }
+    }
+
+    fn add_mine(&mut self, parent:i32, x:i32, y:i32, faction: Faction){
+        self.count += 1;
+        let id = self.count;
+        let m = mine::Mine::new(id, parent, x, y, faction, &mut self.rng);
+        self.mines.push(m);
+    }
+
+    fn blast(&mut self, m: &actor::ActorView, radius: f32, owner: i32){
+        self.add_explosion(m.x as i32, m.y as i32, radius as i32, m.rotation);
+
+        // the blast hits everything inside it that isn't on the same side
+        for a in self.get_within(m.x, m.y, radius).iter(){
+            if a.id == 0 || a.id == m.id || !a.collision_type.is_solid()
+                || m.faction.is_friendly(&a.faction) {
+                continue;
+            }
+            self.pending.push((a.id, PlayerInstructions::Impact(m.x, m.y, owner)));
+            self.pending.push((a.id, PlayerInstructions::Collide(BLAST_DAMAGE)));
+        }
+    }
+
+    fn trigger_mines(&self, views: &Vec<actor::ActorView>, messages:&mut Vec<(i32, PlayerInstructions)>){
+        let mines = ActorManager::get_views(&self.mines);
+
+        for m in mines.iter(){
+            if m.meter < 1.0 {
+                // not armed yet
+                continue;
+            }
+
+            for a in ActorManager::within(views, m.x, m.y, mine::TRIGGER_RADIUS).iter(){
+                if a.id == 0
+                    || a.id == m.parent
+                    || m.faction.is_friendly(&a.faction)
+                    || !a.collision_type.is_solid()
+                    || mines.iter().any(|other| other.id == a.id) {
+                    continue;
+                }
+
+                messages.push((m.id, PlayerInstructions::Trigger));
+                break;
+            }
+        }
+    }
+
+    fn launch_kamikaze(&mut self, boss: &actor::ActorView){
+        // launch from outside the hull, on the side facing the player
+        let dx = self.px - boss.x;
+        let dy = self.py - boss.y;
+        let angle = dx.atan2(dy);
+        let d = boss.height + 150.0;
+        let x = boss.x + angle.sin() * d;
+        let y = boss.y + angle.cos() * d;
+        let target = (self.px, self.py);
+        self.new_kamikaze(x as i32, y as i32, target);
+    }
+
+    fn attach_boss_parts(&mut self, output_messages:&mut Vec<(GameInstructions, actor::ActorView)>){
+        let bosses = ActorManager::get_views(&self.bosses);
+        let target = (self.px, self.py);
+
+        for part in self.boss_parts.iter_mut(){
+            match bosses.iter().find(|b| b.id == part.get_parent()) {
+                Some(boss)  => part.attach(boss, target),
+                None        => {
+                    // the mothership is gone - take the parts with it
+                    part.kill();
+                    output_messages.push((GameInstructions::Explode, part.get_view()));
+                }
+            }
+        }
+    }
+
+    // jump somewhere nearby that nothing is about to fly into, looking further
+    // afield if it's crowded - and not at all if there's nowhere clear
+    fn hyperspace(&mut self, ship: &actor::ActorView){
+        let solids: Vec<actor::ActorView> = self.get().into_iter()
+            .filter(|a| a.id != ship.id && a.collision_type.is_solid())
+            .collect();
+        let rng = &mut self.rng;
+
+        let destination = (0..HYPERSPACE_ATTEMPTS)
+            .map(|i| ActorManager::random_position(rng, (ship.x, ship.y), 1500, 3000 + (i / 10) * 1500))
+            .find(|&(x, y)| solids.iter().all(|a| {
+                let (dx, dy) = (a.x - x as f32, a.y - y as f32);
+                dx * dx + dy * dy > HYPERSPACE_CLEARANCE * HYPERSPACE_CLEARANCE
+            }));
+
+        let (x, y) = match destination {
+            Some(destination) => destination,
+            None              => {
+                self.pending.push((ship.id, PlayerInstructions::HyperspaceBlocked));
+                return;
+            }
+        };
+
+        for s in self.spaceships.iter_mut(){
+            if s.get_id() == ship.id {
+                s.teleport(x as f32, y as f32);
+            }
+        }
+
+        self.explosions.push(explosion::Explosion::new_warp(ship.x as i32, ship.y as i32, 150));
+        self.explosions.push(explosion::Explosion::new_warp(x, y, 150));
+    }
+
+    // a random point between min_distance and max_distance from (cx, cy) - picked
+    // as a distance and a direction, so it never has to try again
+    pub fn random_position<R: Rng>(rng: &mut R, (cx, cy): (f32, f32), min_distance: i32, max_distance: i32) -> (i32, i32){
+        let (min, max) = (min_distance as f32, max_distance.max(min_distance) as f32);
+        // spread evenly over the area, not bunched up in the middle
+        let distance = (min * min + rng.gen::<f32>() * (max * max - min * min)).sqrt();
+        let angle = rng.gen::<f32>() * 2.0 * 3.14159265359;
+
+        ((cx + distance * angle.sin()) as i32, (cy + distance * angle.cos()) as i32)
+    }
+
+    fn add_explosion(&mut self, x:i32, y:i32, d:i32, r:f32){
+        let expl = explosion::Explosion::new(x, y,  d, r);
+        self.explosions.push(expl);
+    }
+
+    fn get_nearest(views: &Vec<actor::ActorView>, actor: &actor::ActorView, range: f32) -> Vec<actor::ActorView>{
+        let mut nearest = ActorManager::within(views, actor.x, actor.y, range);
+        nearest.retain(|enemy| enemy.id != actor.id);
         nearest
     }
 
+    pub fn get_within(&self, x: f32, y: f32, radius: f32) -> Vec<actor::ActorView>{
+        ActorManager::within(&self.get(), x, y, radius)
+    }
+
+    fn within(views: &Vec<actor::ActorView>, x: f32, y: f32, radius: f32) -> Vec<actor::ActorView>{
+        let max_distance = radius * radius;
+        views.iter().filter(|a| {
+            let dx = a.x - x;
+            let dy = a.y - y;
+            dx * dx + dy * dy < max_distance
+        }).cloned().collect()
+    }
+
     fn update_actor_list<T: actor::Actor>(px:f32, py:f32, list:&mut Vec<T>,
                                 messages:&Vec<(i32, PlayerInstructions)>,
                                 output_messages:&mut Vec<(GameInstructions, actor::ActorView)>) -> Vec<T>{
@@ -201,7 +825,8 @@ impl ActorManager {
 
         for actor in list.iter_mut() {
             let a_pos = actor.get_view();
-            if actor.get_id() != 1 && a_pos.collision_type != actor::CollisionType::Collect{
+            // rivals keep racing for the token however far off it takes them
+            if actor.get_id() != 1 && a_pos.collision_type != actor::CollisionType::Collect && a_pos.faction != Faction::Rivals {
                 let x_distance = a_pos.x - px;
                 let y_distance = a_pos.y - py;
                 let distance = x_distance * x_distance + y_distance * y_distance;
@@ -236,3 +861,279 @@ impl ActorManager {
         v
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collisions;
+    use game::Game;
+
+    fn tick(actors: &mut ActorManager, messages: Vec<(i32, PlayerInstructions)>){
+        let mut output_messages = vec!();
+        actors.update(messages, &mut output_messages);
+        actors.process_messages(&mut output_messages);
+    }
+
+    fn rock_parent(actors: &ActorManager, id: i32) -> i32 {
+        actors.get().into_iter().find(|v| v.id == id).unwrap().parent
+    }
+
+    // the player with a still rock just off its nose
+    fn towing_setup() -> (ActorManager, i32) {
+        let mut actors = ActorManager::new();
+        actors.new_player();
+        let id = 50;
+        actors.asteroids.push(asteroid::Asteroid::new_fragment(id, 0, 300, 100.0, 0, (0.0, 0.0), asteroid::Material::Rock, &mut Dice::new(0)));
+        (actors, id)
+    }
+
+    #[test]
+    fn hyperspace_lands_clear_of_everything(){
+        let mut actors = ActorManager::new();
+        actors.new_player();
+        actors.asteroids.push(asteroid::Asteroid::new_fragment(50, 2000, 0, 100.0, 0, (0.0, 0.0), asteroid::Material::Rock, &mut Dice::new(0)));
+        let ship = actors.get_player().unwrap();
+
+        for _ in 0..20 {
+            for s in actors.spaceships.iter_mut() {
+                s.teleport(0.0, 0.0);
+            }
+            actors.hyperspace(&ship);
+            let p = actors.get_player().unwrap();
+            assert!((p.x - 2000.0).powi(2) + p.y.powi(2) > HYPERSPACE_CLEARANCE * HYPERSPACE_CLEARANCE);
+            assert!(p.x * p.x + p.y * p.y > 1500.0 * 1500.0);
+        }
+    }
+
+    #[test]
+    fn hyperspace_refuses_to_jump_into_a_crowd(){
+        let mut actors = ActorManager::new();
+        actors.new_player();
+        let mut id = 100;
+        for x in -20..21 {
+            for y in -20..21 {
+                if x != 0 || y != 0 {
+                    id += 1;
+                    actors.asteroids.push(asteroid::Asteroid::new_fragment(id, x * 400, y * 400, 100.0, 0, (0.0, 0.0), asteroid::Material::Rock, &mut Dice::new(0)));
+                }
+            }
+        }
+        let ship = actors.get_player().unwrap();
+
+        actors.hyperspace(&ship);
+        let p = actors.get_player().unwrap();
+        assert_eq!((p.x, p.y), (0.0, 0.0));
+        assert_eq!(actors.pending, vec!((1, PlayerInstructions::HyperspaceBlocked)));
+    }
+
+    #[test]
+    fn debris_doesnt_count_towards_the_asteroid_cap(){
+        let mut actors = ActorManager::new();
+        actors.asteroids.push(asteroid::Asteroid::new_fragment(50, 0, 0, 100.0, 0, (0.0, 0.0), asteroid::Material::Rock, &mut Dice::new(0)));
+        actors.asteroids.push(asteroid::Asteroid::new_fragment(51, 0, 0, 10.0, 50, (0.0, 0.0), asteroid::Material::Rock, &mut Dice::new(0)));
+
+        assert_eq!(actors.count(&SpawnKind::Asteroid), 1);
+    }
+
+    #[test]
+    fn flinging_a_rock_gives_it_back_its_parent(){
+        let (mut actors, rock) = towing_setup();
+
+        tick(&mut actors, vec!((1, PlayerInstructions::Tractor)));
+        tick(&mut actors, vec!());
+        assert_eq!(rock_parent(&actors, rock), 1);
+
+        tick(&mut actors, vec!((1, PlayerInstructions::Tractor)));
+        tick(&mut actors, vec!());
+        assert_eq!(rock_parent(&actors, rock), 0);
+        assert!(actors.get_tethers().is_empty());
+    }
+
+    #[test]
+    fn a_broken_tether_gives_the_rock_back_its_parent(){
+        let (mut actors, rock) = towing_setup();
+
+        tick(&mut actors, vec!((1, PlayerInstructions::Tractor)));
+        tick(&mut actors, vec!());
+        assert_eq!(rock_parent(&actors, rock), 1);
+
+        // jump well past the breaking point
+        for s in actors.spaceships.iter_mut() {
+            s.teleport(0.0, -TETHER_BREAK * 2.0);
+        }
+        tick(&mut actors, vec!());
+        tick(&mut actors, vec!());
+        assert!(actors.get_tethers().is_empty());
+        assert_eq!(rock_parent(&actors, rock), 0);
+    }
+
+    #[test]
+    fn the_player_input_is_whatever_flies_the_ship(){
+        let mut actors = ActorManager::new();
+        actors.new_player();
+        assert_eq!(actors.get_agent_kind(1), Some(agent::Kind::Keyboard));
+
+        actors.update(vec!((1, PlayerInstructions::Fire)), &mut vec!());
+        assert_eq!(actors.get_player_input(), &vec!(PlayerInstructions::Fire));
+
+        actors.assign(Box::new(agent::Replay::new(1, vec!(vec!(PlayerInstructions::DropMine)))));
+        assert_eq!(actors.get_agent_kind(1), Some(agent::Kind::Replay));
+        actors.update(vec!((1, PlayerInstructions::Fire)), &mut vec!());
+        assert_eq!(actors.get_player_input(), &vec!(PlayerInstructions::DropMine));
+    }
+
+    fn kills(output_messages: &Vec<(GameInstructions, actor::ActorView)>) -> Vec<i32> {
+        output_messages.iter().filter_map(|&(ref msg, _)| match msg {
+            &GameInstructions::Kill(killer) => Some(killer),
+            _                               => None
+        }).collect()
+    }
+
+    #[test]
+    fn ramming_a_rock_to_death_earns_nothing(){
+        let player = spaceship::Spaceship::new(1, 0, 0, 0.0).get_view();
+        let rock = asteroid::Asteroid::new_fragment(50, 0, 0, 100.0, 0, (0.0, 0.0), asteroid::Material::Rock, &mut Dice::new(0)).get_view();
+        let hits = vec!((1, 50), (50, 1));
+
+        let mut output_messages = vec!((GameInstructions::Explode, rock.clone()), (GameInstructions::Explode, player.clone()));
+        ActorManager::credit_kills(&hits, &mut output_messages);
+        assert_eq!(kills(&output_messages), vec!());
+
+        let mut output_messages = vec!((GameInstructions::Explode, rock.clone()));
+        ActorManager::credit_kills(&hits, &mut output_messages);
+        assert_eq!(kills(&output_messages), vec!(1));
+    }
+
+    #[test]
+    fn a_shot_still_counts_if_the_shooter_dies_too(){
+        let player = spaceship::Spaceship::new(1, 0, 0, 0.0).get_view();
+        let rock = asteroid::Asteroid::new_fragment(50, 0, 0, 100.0, 0, (0.0, 0.0), asteroid::Material::Rock, &mut Dice::new(0)).get_view();
+        // the player's bullet hit the rock, and something else got the player
+        let hits = vec!((50, 1), (1, 60));
+
+        let mut output_messages = vec!((GameInstructions::Explode, rock), (GameInstructions::Explode, player));
+        ActorManager::credit_kills(&hits, &mut output_messages);
+        // and whatever got the player gets the credit for that
+        assert_eq!(kills(&output_messages), vec!(1, 60));
+    }
+
+    #[test]
+    fn a_token_two_ships_reach_together_only_goes_to_one(){
+        let mut actors = ActorManager::new();
+        actors.new_player();
+        actors.new_rival(0, 0);
+        actors.tokens = vec!(token::Token::new(500, 0, 0, &mut Dice::new(0)));
+        let mut game = Game::new();
+
+        let mut messages = vec!();
+        collisions::calculate_collisions(&actors, &mut messages);
+        let mut output_messages = vec!();
+        actors.update(messages, &mut output_messages);
+        actors.process_messages(&mut output_messages);
+        game.process_messages(output_messages);
+
+        assert_eq!(game.tokens + game.rival_tokens, 1);
+        assert_eq!(actors.tokens_placed, 1);
+    }
+
+    #[test]
+    fn random_positions_stay_in_the_ring(){
+        let mut rng = Dice::new(3);
+        for _ in 0..1000 {
+            let (x, y) = ActorManager::random_position(&mut rng, (100.0, -100.0), 1500, 3000);
+            let distance = (((x - 100) * (x - 100) + (y + 100) * (y + 100)) as f32).sqrt();
+            assert!(distance >= 1499.0 && distance <= 3001.0, "{}", distance);
+        }
+
+        // rings with no room in them still give an answer
+        assert_eq!(ActorManager::random_position(&mut rng, (0.0, 0.0), 0, 0), (0, 0));
+        let (x, y) = ActorManager::random_position(&mut rng, (0.0, 0.0), 500, 100);
+        assert!((((x * x + y * y) as f32).sqrt() - 500.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn the_same_seed_builds_the_same_world(){
+        let build = || {
+            let mut actors = ActorManager::new();
+            actors.set_seed(Some(9));
+            actors.restart();
+            actors.new_asteroid(500, 500, asteroid::Material::Rock);
+            actors.new_spaceship(-500, 500, Faction::Pirates);
+            let ship = actors.get_player().unwrap();
+            actors.hyperspace(&ship);
+            actors.get()
+        };
+
+        assert_eq!(build(), build());
+    }
+
+    static AVOIDANCE_TEST_TICKS: usize = 1200;
+    static AVOIDANCE_TEST_SHIPS: usize = 20;
+    static AVOIDANCE_TEST_ASTEROIDS: usize = 80;
+    static AVOIDANCE_TEST_SPACING: f32 = 500.0;
+    static AVOIDANCE_TEST_RADIUS: i32 = 3500;
+    static AVOIDANCE_TEST_ATTEMPTS: usize = 100;
+
+    // somewhere in the test field clear of everything already there, so nothing
+    // starts out overlapping - None if the field's too full to find one
+    fn test_position(actors: &ActorManager, rng: &mut Dice) -> Option<(i32, i32)> {
+        let views = actors.get();
+        (0..AVOIDANCE_TEST_ATTEMPTS)
+            .map(|_| ActorManager::random_position(rng, (0.0, 0.0), 0, AVOIDANCE_TEST_RADIUS))
+            .find(|&(x, y)| views.iter().filter(|v| v.collision_type.is_solid()).all(|v| {
+                let (dx, dy) = (v.x - x as f32, v.y - y as f32);
+                (dx * dx + dy * dy).sqrt() > AVOIDANCE_TEST_SPACING
+            }))
+    }
+
+    // flies traders through a dense field with nowhere else to go, and counts
+    // how many are still flying at the end
+    fn avoidance_survivors(avoidance: bool) -> usize {
+        let mut rng = Dice::new(1);
+        let mut actors = ActorManager::new();
+        actors.set_seed(Some(1));
+        actors.set_avoidance(avoidance);
+
+        for _ in 0..AVOIDANCE_TEST_SHIPS {
+            let (x, y) = test_position(&actors, &mut rng).expect("no clear spot for a ship");
+            actors.new_spaceship(x, y, Faction::Traders);
+        }
+
+        let mut lost: Vec<i32> = vec!();
+        for _ in 0..AVOIDANCE_TEST_TICKS {
+            // keep the field topped up as rocks break up or drift off, as far as
+            // there's room - a rock dropped on top of a ship would skew the count
+            while actors.count(&SpawnKind::Asteroid) < AVOIDANCE_TEST_ASTEROIDS {
+                match test_position(&actors, &mut rng) {
+                    Some((x, y)) => actors.new_asteroid(x, y, asteroid::Material::Rock),
+                    None         => break
+                }
+            }
+
+            // nobody gets out of the field without going through it
+            actors.wrap_ships((0.0, 0.0), AVOIDANCE_TEST_RADIUS as f32);
+
+            let mut messages = vec!();
+            collisions::calculate_collisions(&actors, &mut messages);
+
+            let mut output_messages = vec!();
+            actors.update(messages, &mut output_messages);
+            actors.process_messages(&mut output_messages);
+
+            for &(ref msg, ref v) in output_messages.iter() {
+                match msg {
+                    &GameInstructions::Explode if v.faction == Faction::Traders && !lost.contains(&v.id) => lost.push(v.id),
+                    _ => ()
+                }
+            }
+        }
+
+        AVOIDANCE_TEST_SHIPS - lost.len()
+    }
+
+    #[test]
+    fn avoidance_keeps_more_ships_flying(){
+        let (on, off) = (avoidance_survivors(true), avoidance_survivors(false));
+        assert!(on > off, "avoidance on: {} survived, off: {}", on, off);
+    }
+}
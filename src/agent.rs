@@ -0,0 +1,156 @@
+use actor::ActorView;
+use messages::PlayerInstructions;
+use squadron::Order;
+use spaceship_agent;
+use spaceship_agent::Context;
+use spaceship_agent::Skill;
+use spaceship_agent::Steer;
+
+static AUTOPILOT_SPEED: f32 = 40.0;
+
+// everything a ship's controller gets to go on for one tick
+pub struct Surroundings<'a> {
+    pub nearby: &'a Vec<ActorView>,
+    pub collectables: &'a Vec<ActorView>,
+    // what came in from the keyboard this tick
+    pub input: &'a Vec<PlayerInstructions>,
+    pub order: Option<&'a Order>,
+    pub avoidance: bool
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Ai,
+    Keyboard,
+    Replay,
+    Autopilot
+}
+
+// anything that can fly a ship - hand one to ActorManager::assign and it
+// takes over that ship, the player's included
+pub trait Agent {
+    // the ship this agent flies
+    fn get_id(&self) -> i32;
+    fn get_kind(&self) -> Kind;
+    fn control(&mut self, ship: &ActorView, surroundings: &Surroundings, player_messages: &mut Vec<(i32, PlayerInstructions)>);
+}
+
+impl Agent for spaceship_agent::Pilot {
+    fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    fn get_kind(&self) -> Kind {
+        Kind::Ai
+    }
+
+    fn control(&mut self, ship: &ActorView, surroundings: &Surroundings, player_messages: &mut Vec<(i32, PlayerInstructions)>){
+        self.think(ship, surroundings.nearby, surroundings.collectables, surroundings.avoidance, surroundings.order, player_messages);
+    }
+}
+
+// whoever's at the keys
+pub struct Keyboard {
+    id: i32
+}
+
+impl Keyboard {
+    pub fn new(id: i32) -> Keyboard {
+        Keyboard { id: id }
+    }
+}
+
+impl Agent for Keyboard {
+    fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    fn get_kind(&self) -> Kind {
+        Kind::Keyboard
+    }
+
+    fn control(&mut self, _: &ActorView, surroundings: &Surroundings, player_messages: &mut Vec<(i32, PlayerInstructions)>){
+        for instruction in surroundings.input.iter() {
+            player_messages.push((self.id, instruction.clone()));
+        }
+    }
+}
+
+// plays back recorded input, one frame per tick, then lets go of the controls
+pub struct Replay {
+    id: i32,
+    frames: Vec<Vec<PlayerInstructions>>,
+    tick: usize
+}
+
+impl Replay {
+    pub fn new(id: i32, frames: Vec<Vec<PlayerInstructions>>) -> Replay {
+        Replay { id: id, frames: frames, tick: 0 }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.frames.len()
+    }
+}
+
+impl Agent for Replay {
+    fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    fn get_kind(&self) -> Kind {
+        Kind::Replay
+    }
+
+    fn control(&mut self, _: &ActorView, _: &Surroundings, player_messages: &mut Vec<(i32, PlayerInstructions)>){
+        if self.tick < self.frames.len() {
+            for instruction in self.frames[self.tick].iter() {
+                player_messages.push((self.id, instruction.clone()));
+            }
+        } else if self.tick == self.frames.len() {
+            for instruction in vec!(PlayerInstructions::StopIncreaseThrottle, PlayerInstructions::StopDecreaseThrottle,
+                                    PlayerInstructions::StopRotateLeft, PlayerInstructions::StopRotateRight) {
+                player_messages.push((self.id, instruction));
+            }
+        }
+        self.tick += 1;
+    }
+}
+
+// flies to the nearest token, steering round whatever's in the way
+pub struct Autopilot {
+    id: i32,
+    skill: Skill
+}
+
+impl Autopilot {
+    pub fn new(id: i32) -> Autopilot {
+        Autopilot { id: id, skill: Skill::new() }
+    }
+}
+
+impl Agent for Autopilot {
+    fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    fn get_kind(&self) -> Kind {
+        Kind::Autopilot
+    }
+
+    fn control(&mut self, ship: &ActorView, surroundings: &Surroundings, player_messages: &mut Vec<(i32, PlayerInstructions)>){
+        let context = Context::new(ship, surroundings.nearby, surroundings.collectables, &self.skill, None);
+
+        let mut steer = Steer::new();
+        match context.token {
+            Some(ref token) => spaceship_agent::fly_to(ship, (token.x, token.y), AUTOPILOT_SPEED, &mut steer),
+            None            => ()
+        }
+
+        if surroundings.avoidance {
+            spaceship_agent::avoid(&context, &mut steer);
+        }
+
+        steer.apply(ship, self.skill.firing_window, player_messages);
+    }
+}
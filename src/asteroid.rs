@@ -1,12 +1,55 @@
 use actor::Actor;
 use actor::ActorView;
-use rand;
 use rand::Rng;
+use dice::Dice;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use actor;
 use messages::PlayerInstructions;
 use messages::GameInstructions;
+use faction::Faction;
 
 static PI : f32 = 3.14159265359;
+static MIN_SIZE: f32 = 30.0;
+// anything smaller just shatters, so splitting always comes to an end
+static MIN_SPLIT_SIZE: f32 = 60.0;
+static DEBRIS_LIFETIME: i32 = 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Material {
+    Rock,
+    Ice,
+    Metal,
+    Volatile
+}
+
+impl Material {
+    fn color(&self) -> Vec<f32> {
+        match *self {
+            Material::Rock      => vec!(0.4, 0.3, 0.3),
+            Material::Ice       => vec!(0.55, 0.7, 0.85),
+            Material::Metal     => vec!(0.5, 0.5, 0.55),
+            Material::Volatile  => vec!(0.8, 0.35, 0.1)
+        }
+    }
+
+    fn hit_points(&self) -> i32 {
+        match *self {
+            Material::Metal     => 4,
+            _                   => 1
+        }
+    }
+
+    // how many fragments, and how big relative to the parent
+    fn fragments(&self) -> ((i32, i32), (f32, f32)) {
+        match *self {
+            Material::Rock      => ((2, 5), (0.35, 0.6)),
+            Material::Ice       => ((4, 8), (0.2, 0.35)),
+            Material::Metal     => ((2, 3), (0.4, 0.6)),
+            Material::Volatile  => ((0, 1), (0.0, 0.0))
+        }
+    }
+}
 
 
 #[derive(Debug, Clone, PartialEq)]
@@ -23,49 +66,38 @@ pub struct Asteroid{
     vy: f32,
     width: f32,
     height: f32,
-    parent: i32
+    parent: i32,
+    // our own parent, kept while whoever's towing us stands in for it
+    towed_from: Option<i32>,
+    impact: Option<(f32, f32)>,
+    debris: bool,
+    age: i32,
+    material: Material,
+    health: i32,
+    hit_timer: i32,
+    // rolled for how we break up
+    dice: Dice
 }
 
 impl Asteroid{
-    pub fn new(id: i32, x: i32, y: i32) -> Asteroid{
+    pub fn new<R: Rng>(id: i32, x: i32, y: i32, material: Material, rng: &mut R) -> Asteroid{
 
-        let d = rand::thread_rng().gen_range(40.0f32, 180.0);
-        Asteroid::new_with_d(id, x, y, d, 0)
+        let d = rng.gen_range(40.0f32, 180.0);
+        let vx = rng.gen_range(-30.0f32, 30.0);
+        let vy = rng.gen_range(-30.0f32, 30.0);
+        Asteroid::new_fragment(id, x, y, d, 0, (vx, vy), material, rng)
     }
-    pub fn new_with_d(id: i32, x: i32, y: i32, d: f32, parent: i32) -> Asteroid {
-
-        let r = rand::thread_rng().gen_range(-5.0f32, 5.0);
-        let vx = rand::thread_rng().gen_range(-30.0f32, 30.0);
-        let vy = rand::thread_rng().gen_range(-30.0f32, 30.0);
-
-        let max = d / 2000.0;
-        let min = max / 2.0;
 
-        let shape = vec!(
-            -min,  max,
-            min,   max,
-            max,   0.0,
+    pub fn new_fragment<R: Rng>(id: i32, x: i32, y: i32, d: f32, parent: i32, (vx, vy): (f32, f32), material: Material, rng: &mut R) -> Asteroid {
 
-            max,   0.00,
-            min,  -max,
-            -min, -max,
+        let r = rng.gen_range(-5.0f32, 5.0);
+        let debris = d < MIN_SIZE;
+        let color = material.color();
 
-            -min, -max,
-            -max,  0.00,
-            -min,  max,
-
-            -min,  max,
-             max,  0.00,
-             -min, -max
-
-
-        );
-
-        let color = vec!(0.4, 0.3, 0.3);
         Asteroid{
             id: id, x: x as f32, y: y as f32,
             rotation: 0.0,
-            shape: shape,
+            shape: Asteroid::generate_shape(id as u64, d),
             is_alive: true,
             color: color,
             r_speed: r,
@@ -73,7 +105,96 @@ impl Asteroid{
             vy: vy,
             width: d,
             height: d,
-            parent: parent
+            parent: parent,
+            towed_from: None,
+            impact: None,
+            debris: debris,
+            age: 0,
+            health: material.hit_points(),
+            material: material,
+            hit_timer: 0,
+            dice: Dice::from_rng(rng)
+        }
+    }
+
+    // an irregular polygon drawn as a fan of triangles around the centre
+    // the same seed always gives the same rock
+    fn generate_shape(seed: u64, d: f32) -> Vec<f32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let max = d / 2000.0;
+        let points = rng.gen_range(7, 13);
+
+        let mut outline = vec!();
+        for i in 0..points {
+            let angle = (i as f32 + rng.gen_range(-0.3f32, 0.3)) * 2.0 * PI / points as f32;
+            let radius = max * rng.gen_range(0.65f32, 1.0);
+            outline.push((angle.sin() * radius, angle.cos() * radius));
+        }
+
+        let mut shape = vec!();
+        for i in 0..points {
+            let (x1, y1) = outline[i];
+            let (x2, y2) = outline[(i + 1) % points];
+            shape.extend(vec!(0.0, 0.0, x1, y1, x2, y2).into_iter());
+        }
+        shape
+    }
+
+    // harmless bits that fade away - not worth counting as rocks
+    pub fn is_debris(&self) -> bool {
+        self.debris
+    }
+
+    // shoved off a shield centred on (sx, sy)
+    fn push_away(&mut self, (sx, sy): (f32, f32)){
+        let dx = self.x - sx;
+        let dy = self.y - sy;
+        let length = (dx * dx + dy * dy).sqrt().max(0.001);
+        let (nx, ny) = (dx / length, dy / length);
+
+        let dot = self.vx * nx + self.vy * ny;
+        if dot < 0.0 {
+            self.vx -= 2.0 * dot * nx;
+            self.vy -= 2.0 * dot * ny;
+        }
+
+        let push = 400.0 / (self.width + 50.0);
+        self.vx += nx * push;
+        self.vy += ny * push;
+    }
+
+    fn split(&mut self, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        let ((min_pieces, max_pieces), (min_d, max_d)) = self.material.fragments();
+        if self.width < MIN_SPLIT_SIZE {
+            return;
+        }
+
+        let pieces = self.dice.gen_range(min_pieces, max_pieces);
+        if pieces < 2 {
+            return;
+        }
+
+        // fragments fly away from wherever we were hit
+        let (ix, iy) = self.impact.unwrap_or((self.x - self.vx, self.y - self.vy));
+        let away = (self.x - ix).atan2(self.y - iy);
+        let spread = 1.2;
+
+        for i in 0..pieces {
+            let offset = (i as f32 / (pieces - 1) as f32 - 0.5) * spread;
+            let angle = away + offset + self.dice.gen_range(-0.2f32, 0.2);
+            let d = self.width * self.dice.gen_range(min_d, max_d);
+            let impulse = self.dice.gen_range(5.0f32, 15.0) * 100.0 / (d + 50.0);
+
+            let mut fragment = self.get_view();
+            fragment.parent = self.id;
+            fragment.x = self.x + angle.sin() * self.width * 0.4;
+            fragment.y = self.y + angle.cos() * self.width * 0.4;
+            fragment.vx = self.vx + angle.sin() * impulse;
+            fragment.vy = self.vy + angle.cos() * impulse;
+            fragment.width = d;
+            fragment.height = d;
+
+            output_messages.push((GameInstructions::NewAsteroid(self.material.clone()), fragment));
         }
     }
 }
@@ -85,35 +206,80 @@ impl Actor for Asteroid{
         self.x += self.vx;
         self.y += self.vy;
         self.rotation += self.r_speed;
+
+        if self.hit_timer > 0 {
+            self.hit_timer -= 1;
+        }
+
+        if self.debris {
+            self.age += 1;
+            let fade = 1.0 - self.age as f32 / DEBRIS_LIFETIME as f32;
+            self.color = self.material.color().iter().map(|c| 0.1 + (c - 0.1) * fade * 0.7).collect();
+
+            if self.age > DEBRIS_LIFETIME {
+                self.is_alive = false;
+            }
+        }
     }
 
     fn get_view(&self) -> ActorView {
         ActorView {
             id: self.id,
             parent: self.parent,
+            faction: Faction::Unaligned,
             x: self.x,
             y: self.y,
+            vx: self.vx,
+            vy: self.vy,
             width: self.width,
             height: self.height,
             rotation: (self.rotation * PI) / 180.0,
             shape: self.shape.clone(),
-            color: self.color.clone(),
-            collision_type: actor::CollisionType::Collide,
+            color: if self.hit_timer > 0 { vec!(0.9, 0.9, 0.9) } else { self.color.clone() },
+            collision_type: if self.debris { actor::CollisionType::Ignore } else { actor::CollisionType::Collide },
             show_secondary: false,
             secondary_shape: None,
             secondary_color: None,
-            meter: 0.0
+            meter: 0.0,
+            cooldown: 0.0,
+            damage: self.width * 0.4,
+            hull: 1.0
         }
     }
 
     fn execute(&mut self, message: &PlayerInstructions, output_messages:&mut Vec<(GameInstructions, ActorView)>){
         match message {
-            &PlayerInstructions::Collide => {
+            &PlayerInstructions::Impact(x, y, _) => self.impact = Some((x, y)),
+            &PlayerInstructions::Deflect(sx, sy, _, _, _) => self.push_away((sx, sy)),
+            // whoever's towing it owns it - no hitting them, and credit for what it hits
+            &PlayerInstructions::Tether(owner) => {
+                                            if self.towed_from.is_none() {
+                                                self.towed_from = Some(self.parent);
+                                            }
+                                            self.parent = owner;
+                                        },
+            &PlayerInstructions::Release => match self.towed_from.take() {
+                                            Some(parent) => self.parent = parent,
+                                            None         => ()
+                                        },
+            &PlayerInstructions::Collide(_) => {
+                                            if self.debris {
+                                                return;
+                                            }
+
+                                            self.health -= 1;
+                                            self.hit_timer = 4;
+                                            if self.health > 0 {
+                                                return;
+                                            }
+
                                             self.is_alive = false;
-                                            if self.width > 100.0 {
-                                                output_messages.push((GameInstructions::NewAsteroid, self.get_view().clone()));
+                                            self.split(output_messages);
+                                            if self.material == Material::Volatile {
+                                                output_messages.push((GameInstructions::Blast, self.get_view().clone()));
+                                            } else {
+                                                output_messages.push((GameInstructions::Explode, self.get_view().clone()));
                                             }
-                                            output_messages.push((GameInstructions::Explode, self.get_view().clone()));
                                         },
             _                           => ()
         };
@@ -123,6 +289,11 @@ impl Actor for Asteroid{
         self.is_alive = false;
     }
 
+    fn apply_impulse(&mut self, dvx: f32, dvy: f32){
+        self.vx += dvx;
+        self.vy += dvy;
+    }
+
     fn get_id(&self) -> i32{
         self.id
     }
@@ -132,3 +303,90 @@ impl Actor for Asteroid{
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_hands_the_rock_back_to_its_own_parent(){
+        let mut rock = Asteroid::new_fragment(5, 0, 0, 100.0, 3, (0.0, 0.0), Material::Rock, &mut Dice::new(0));
+        let mut out = vec!();
+
+        rock.execute(&PlayerInstructions::Tether(1), &mut out);
+        assert_eq!(rock.get_view().parent, 1);
+
+        // grabbed again by someone else before being let go
+        rock.execute(&PlayerInstructions::Tether(7), &mut out);
+        rock.execute(&PlayerInstructions::Release, &mut out);
+        assert_eq!(rock.get_view().parent, 3);
+    }
+
+    fn fragments(out: &Vec<(GameInstructions, ActorView)>) -> Vec<ActorView> {
+        out.iter().filter_map(|&(ref m, ref v)| match *m {
+            GameInstructions::NewAsteroid(_) => Some(v.clone()),
+            _                                => None
+        }).collect()
+    }
+
+    #[test]
+    fn big_rocks_split_into_smaller_ones(){
+        let mut rock = Asteroid::new_fragment(5, 0, 0, 150.0, 0, (0.0, 0.0), Material::Rock, &mut Dice::new(0));
+        let mut out = vec!();
+        rock.execute(&PlayerInstructions::Collide(10.0), &mut out);
+
+        let pieces = fragments(&out);
+        assert!(pieces.len() >= 2);
+        assert!(pieces.iter().all(|p| p.width < 150.0 && p.parent == 5));
+    }
+
+    #[test]
+    fn small_rocks_shatter_without_splitting(){
+        let mut rock = Asteroid::new_fragment(5, 0, 0, MIN_SPLIT_SIZE - 1.0, 0, (0.0, 0.0), Material::Ice, &mut Dice::new(0));
+        let mut out = vec!();
+        rock.execute(&PlayerInstructions::Collide(10.0), &mut out);
+
+        assert!(!rock.is_alive());
+        assert!(fragments(&out).is_empty());
+    }
+
+    // how many explosions and blasts came out
+    fn bangs(out: &Vec<(GameInstructions, ActorView)>) -> (usize, usize) {
+        out.iter().fold((0, 0), |(explosions, blasts), &(ref m, _)| match *m {
+            GameInstructions::Explode => (explosions + 1, blasts),
+            GameInstructions::Blast   => (explosions, blasts + 1),
+            _                         => (explosions, blasts)
+        })
+    }
+
+    #[test]
+    fn metal_takes_a_beating_before_it_breaks(){
+        let mut rock = Asteroid::new_fragment(5, 0, 0, 150.0, 0, (0.0, 0.0), Material::Metal, &mut Dice::new(0));
+        let mut out = vec!();
+        for _ in 1..Material::Metal.hit_points() {
+            rock.execute(&PlayerInstructions::Collide(10.0), &mut out);
+        }
+        assert!(rock.is_alive());
+        assert!(out.is_empty());
+
+        rock.execute(&PlayerInstructions::Collide(10.0), &mut out);
+        assert!(!rock.is_alive());
+        assert_eq!(bangs(&out), (1, 0));
+    }
+
+    #[test]
+    fn volatile_rocks_go_up_with_a_blast(){
+        let mut rock = Asteroid::new_fragment(5, 0, 0, 150.0, 0, (0.0, 0.0), Material::Volatile, &mut Dice::new(0));
+        let mut out = vec!();
+        rock.execute(&PlayerInstructions::Collide(10.0), &mut out);
+
+        assert!(!rock.is_alive());
+        assert_eq!(bangs(&out), (0, 1));
+
+        // where ordinary rock just explodes
+        let mut rock = Asteroid::new_fragment(6, 0, 0, 150.0, 0, (0.0, 0.0), Material::Rock, &mut Dice::new(0));
+        let mut out = vec!();
+        rock.execute(&PlayerInstructions::Collide(10.0), &mut out);
+        assert_eq!(bangs(&out), (1, 0));
+    }
+}
@@ -1,5 +1,6 @@
-use rand;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 
 static RADIUS : f32 = 0.008;
 
@@ -14,7 +15,8 @@ pub struct Background{
     num : usize,
     shape: Vec<f32>,
     stars: Vec<(f32, f32)>,
-    color: Vec<f32>
+    color: Vec<f32>,
+    rng: StdRng
 }
 
 impl Background{
@@ -31,10 +33,17 @@ impl Background{
                 0.0, RADIUS
             ),
             color: vec!(0.5, 0.5, 0.4),
-            stars: vec!()
+            stars: vec!(),
+            rng: StdRng::from_entropy()
         }
     }
 
+    // start the star field over from a fixed seed
+    pub fn seed(&mut self, seed: u64){
+        self.rng = StdRng::seed_from_u64(seed);
+        self.stars = vec!();
+    }
+
     pub fn get(&self) -> Vec<BackgroundElement>{
         let mut output = vec!();
         for &(x, y) in self.stars.iter(){
@@ -56,8 +65,8 @@ impl Background{
         let max_y = cy as i32 + 8000;
 
         while self.stars.len() < self.num {
-            let x = rand::thread_rng().gen_range(min_x, max_x) as f32;
-            let y = rand::thread_rng().gen_range(min_y, max_y) as f32;
+            let x = self.rng.gen_range(min_x, max_x) as f32;
+            let y = self.rng.gen_range(min_y, max_y) as f32;
 
             self.stars.push((x, y));
         }
@@ -71,8 +80,8 @@ impl Background{
         let min_distance = 5500 * 5500; // square instead of sqrt on distance
 
         while self.stars.len() < self.num {
-            let x = rand::thread_rng().gen_range(min_x, max_x) as f32;
-            let y = rand::thread_rng().gen_range(min_y, max_y) as f32;
+            let x = self.rng.gen_range(min_x, max_x) as f32;
+            let y = self.rng.gen_range(min_y, max_y) as f32;
             
             let x_dis = (x - cx) as i32;
             let y_dis = (y - cy) as i32;
@@ -3,8 +3,10 @@ use actor::ActorView;
 use actor;
 use messages::PlayerInstructions;
 use messages::GameInstructions;
+use faction::Faction;
 
 static PI : f32 = 3.14159265359;
+pub static SPEED: f32 = 100.0;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Bullet{
@@ -17,12 +19,13 @@ pub struct Bullet{
     shape: Vec<f32>,
     is_alive:bool,
     parent: i32,
+    faction: Faction,
     color: Vec<f32>
 }
 
 
 impl Bullet{
-    pub fn new(id: i32, parent: i32, x: i32, y: i32, rotation: f32) -> Bullet {
+    pub fn new(id: i32, parent: i32, x: i32, y: i32, rotation: f32, faction: Faction) -> Bullet {
         let shape = vec!(
             0.0,  0.005,
             0.005, -0.005,
@@ -31,13 +34,12 @@ impl Bullet{
 
         let color = vec!(0.2, 0.8, 0.2);
 
-        let acc = 100.0;
         let (dirx, diry) = Bullet::get_rotate_vec(rotation);
-        let acc_x = acc * dirx;
-        let acc_y = acc * diry;
+        let acc_x = SPEED * dirx;
+        let acc_y = SPEED * diry;
 
         Bullet{
-            id: id, parent: parent, x: x as f32, y: y as f32,
+            id: id, parent: parent, faction: faction, x: x as f32, y: y as f32,
             rotation: rotation, acc_x: acc_x, acc_y: acc_y,
             shape: shape,
             is_alive: true,
@@ -45,6 +47,24 @@ impl Bullet{
         }
     }
 
+    // bounce off a shield centred on (sx, sy) - the bullet now belongs to whoever shielded it
+    fn deflect(&mut self, (sx, sy): (f32, f32), owner: i32, faction: Faction){
+        let dx = self.x - sx;
+        let dy = self.y - sy;
+        let length = (dx * dx + dy * dy).sqrt().max(0.001);
+        let (nx, ny) = (dx / length, dy / length);
+
+        let dot = self.acc_x * nx + self.acc_y * ny;
+        if dot < 0.0 {
+            self.acc_x -= 2.0 * dot * nx;
+            self.acc_y -= 2.0 * dot * ny;
+        }
+
+        self.rotation = self.acc_x.atan2(self.acc_y) * 180.0 / PI;
+        self.parent = owner;
+        self.faction = faction;
+    }
+
     fn get_rotate_vec(rotation:f32) -> (f32, f32){
         let r = (rotation * PI) / 180.0;
         (r.sin(), r.cos())
@@ -64,8 +84,11 @@ impl Actor for Bullet{
         ActorView {
             id: self.id,
             parent: self.parent,
+            faction: self.faction.clone(),
             x: self.x,
             y: self.y,
+            vx: self.acc_x,
+            vy: self.acc_y,
             width: 10.0,
             height: 10.0,
             rotation: (self.rotation * PI) / 180.0,
@@ -75,13 +98,17 @@ impl Actor for Bullet{
             show_secondary: false,
             secondary_shape: None,
             secondary_color: None,
-            meter: 0.0
+            meter: 0.0,
+            cooldown: 0.0,
+            damage: 25.0,
+            hull: 1.0
         }
     }
 
     fn execute(&mut self, message: &PlayerInstructions, _:&mut Vec<(GameInstructions, ActorView)>){
         match message {
-            &PlayerInstructions::Collide   => self.is_alive = false,
+            &PlayerInstructions::Collide(_) => self.is_alive = false,
+            &PlayerInstructions::Deflect(sx, sy, owner, ref faction, _) => self.deflect((sx, sy), owner, faction.clone()),
             _                           => ()
         };
     }
@@ -0,0 +1,75 @@
+use actor;
+use actor_manager::ActorManager;
+use faction::Faction;
+use messages::PlayerInstructions;
+
+pub fn calculate_collisions(actor_manager: &ActorManager, messages: &mut Vec<(i32, PlayerInstructions)>){
+
+    let actors = actor_manager.get();
+
+    // tokens already taken this tick - only the first ship to reach one gets it
+    let mut collected = vec!();
+
+    for a1 in actors.iter(){
+
+        // todo - if we count the top iter(), we can slice this one
+        //      - to only match remaining actors
+        //      - would then need to push a2.id messages on collision
+
+        for a2 in actors.iter(){
+
+            if     a1.id    == 0
+                || a2.id    == 0
+                || a1.id    == a2.id
+                || a1.id    == a2.parent
+                || a2.id    == a1.parent
+                || (a1.parent != 0 && a1.parent == a2.parent)
+                || a1.faction.is_friendly(&a2.faction)
+                || a1.collision_type == actor::CollisionType::Ignore
+                || a1.collision_type == actor::CollisionType::Pickup
+                || a2.collision_type == actor::CollisionType::Ignore {
+                continue;
+            }
+
+            if (a1.x - a2.x).abs() as usize > 1000 || (a1.y - a2.y).abs() as usize > 1000 {
+                continue;
+            }
+
+            if a1.x + a1.width > a2.x - a2.width && a1.x - a1.width < a2.x + a2.width
+              && a1.y + a1.height > a2.y - a2.height && a1.y - a1.height < a2.y + a2.height {
+
+                // within bounding box - pixel perfect that shit
+                // ...
+
+                match a2.collision_type{
+                    actor::CollisionType::Collide => {
+                        let owner = if a2.parent != 0 { a2.parent } else { a2.id };
+                        messages.push((a1.id, PlayerInstructions::Impact(a2.x, a2.y, owner)));
+                        messages.push((a1.id, PlayerInstructions::Collide(actor::impact_damage(a1, a2))));
+                    },
+                    actor::CollisionType::Shield => {
+                        let owner = if a2.parent != 0 { a2.parent } else { a2.id };
+                        messages.push((a1.id, PlayerInstructions::Impact(a2.x, a2.y, owner)));
+                        messages.push((a1.id, PlayerInstructions::Deflect(a2.x, a2.y, a2.id, a2.faction.clone(), actor::impact_damage(a1, a2))));
+                    },
+                    actor::CollisionType::Collect => {
+                        // only the player and rivals take tokens
+                        if (a1.id == 1 || a1.faction == Faction::Rivals) && !collected.contains(&a2.id) {
+                            collected.push(a2.id);
+                            messages.push((a1.id, PlayerInstructions::Collect(a2.id)));
+                        }
+                    },
+                    actor::CollisionType::Pickup => {
+                        // only the player scoops up loot
+                        if a1.id == 1 {
+                            messages.push((a2.id, PlayerInstructions::PickedUp(a1.id)));
+                        }
+                    },
+                    _              => ()
+                }
+
+            }
+        }
+    }
+
+}
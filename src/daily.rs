@@ -0,0 +1,229 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use spaceship;
+use spaceship::ShipStats;
+use director::Director;
+use director::SpawnKind;
+
+static MAX_MODIFIERS: usize = 2;
+static LOW_GRAVITY_DRAG: f32 = 0.998;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Modifier {
+    DoubleAsteroids,
+    NoShield,
+    LowGravity,
+    KamikazeRain
+}
+
+impl Modifier {
+    pub fn all() -> Vec<Modifier> {
+        vec!(Modifier::DoubleAsteroids, Modifier::NoShield, Modifier::LowGravity, Modifier::KamikazeRain)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Modifier::DoubleAsteroids   => "double asteroids",
+            Modifier::NoShield          => "no shield",
+            Modifier::LowGravity        => "low gravity",
+            Modifier::KamikazeRain      => "kamikaze rain"
+        }
+    }
+}
+
+// one fixed world per day - everyone playing on the same date
+// gets the same seed and the same modifiers
+pub struct Daily{
+    pub date: String,
+    pub seed: u64,
+    pub modifiers: Vec<Modifier>,
+    results: BestScores
+}
+
+impl Daily{
+    pub fn new(date: String, days: u64) -> Daily {
+        let seed = days.wrapping_mul(2654435761);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut available = Modifier::all();
+        let mut modifiers = vec!();
+        let count = rng.gen_range(1, MAX_MODIFIERS + 1);
+        for _ in 0..count {
+            let i = rng.gen_range(0, available.len());
+            modifiers.push(available.remove(i));
+        }
+
+        Daily {
+            date: date,
+            seed: seed,
+            modifiers: modifiers,
+            results: BestScores::new()
+        }
+    }
+
+    pub fn has(&self, modifier: &Modifier) -> bool {
+        self.modifiers.contains(modifier)
+    }
+
+    // everyone flies the same ship - upgrades don't count here
+    pub fn get_stats(&self) -> ShipStats {
+        let mut stats = ShipStats::new();
+        if self.has(&Modifier::NoShield) {
+            stats.shield_time = 0;
+        }
+        stats
+    }
+
+    pub fn apply(&self, director: &mut Director){
+        if self.has(&Modifier::DoubleAsteroids) {
+            // twice the rocks, with room made for them
+            director.boost(&SpawnKind::Asteroid, 2.0);
+            director.set_budget_scale(1.75);
+        }
+
+        if self.has(&Modifier::KamikazeRain) {
+            director.boost(&SpawnKind::Kamikaze, 4.0);
+        }
+    }
+
+    pub fn get_drag(&self) -> f32 {
+        if self.has(&Modifier::LowGravity) { LOW_GRAVITY_DRAG } else { spaceship::DRAG }
+    }
+
+    // best scores, one `date score` per line
+    pub fn load_results(&mut self, path: &str){
+        self.results = BestScores::load(path);
+    }
+
+    pub fn save_results(&self, path: &str) -> io::Result<()> {
+        self.results.save(path)
+    }
+
+    pub fn best(&self) -> Option<usize> {
+        self.results.best(&self.date)
+    }
+
+    // returns true for a new best on today's challenge
+    pub fn record(&mut self, score: usize) -> bool {
+        self.results.record(&self.date, score)
+    }
+
+    pub fn summary(&self) -> String {
+        let names: Vec<&str> = self.modifiers.iter().map(|m| m.name()).collect();
+        let best = match self.best() {
+            Some(score) => format!("{}", score),
+            None        => "-".to_string()
+        };
+        format!("daily {} [{}] - best [{}]", self.date, names.join(", "), best)
+    }
+}
+
+// the best score for each key, saved one `key score` per line
+#[derive(Clone, Debug, PartialEq)]
+pub struct BestScores {
+    scores: Vec<(String, usize)>
+}
+
+impl BestScores {
+    pub fn new() -> BestScores {
+        BestScores { scores: vec!() }
+    }
+
+    // nothing saved yet is no scores, not an error
+    pub fn load(path: &str) -> BestScores {
+        let mut contents = String::new();
+
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            Ok(_)   => (),
+            Err(_)  => return BestScores::new()
+        }
+
+        BestScores {
+            scores: contents.lines().filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                match (parts.next(), parts.next().and_then(|s| s.parse().ok())) {
+                    (Some(key), Some(score)) => Some((key.to_string(), score)),
+                    _                        => None
+                }
+            }).collect()
+        }
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut f = File::create(path)?;
+        for &(ref key, score) in self.scores.iter() {
+            write!(f, "{} {}\n", key, score)?;
+        }
+        Ok(())
+    }
+
+    pub fn best(&self, key: &str) -> Option<usize> {
+        self.scores.iter().find(|&&(ref k, _)| k == key).map(|&(_, score)| score)
+    }
+
+    // returns true for a new best under this key
+    pub fn record(&mut self, key: &str, score: usize) -> bool {
+        match self.scores.iter().position(|&(ref k, _)| k == key) {
+            Some(i) => {
+                if score <= self.scores[i].1 {
+                    return false;
+                }
+                self.scores[i].1 = score;
+            },
+            None    => self.scores.push((key.to_string(), score))
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn the_same_day_builds_the_same_challenge(){
+        let today = Daily::new("2024-03-01".to_string(), 19783);
+        let again = Daily::new("2024-03-01".to_string(), 19783);
+        assert_eq!((today.seed, &today.modifiers), (again.seed, &again.modifiers));
+
+        let tomorrow = Daily::new("2024-03-02".to_string(), 19784);
+        assert!(tomorrow.seed != today.seed);
+
+        for d in [&today, &tomorrow].iter() {
+            assert!(d.modifiers.len() >= 1 && d.modifiers.len() <= MAX_MODIFIERS);
+            assert!(d.modifiers.iter().all(|m| d.modifiers.iter().filter(|&n| n == m).count() == 1));
+        }
+    }
+
+    #[test]
+    fn results_survive_a_save_and_load(){
+        let path = env::temp_dir().join(format!("rusteroids-{}-results_survive_a_save_and_load.daily", process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut yesterday = Daily::new("2024-03-01".to_string(), 19783);
+        assert_eq!(yesterday.best(), None);
+        assert!(yesterday.record(40));
+        assert!(!yesterday.record(30));
+        yesterday.save_results(path).unwrap();
+
+        let mut today = Daily::new("2024-03-02".to_string(), 19784);
+        today.load_results(path);
+        assert_eq!(today.best(), None);
+        assert!(today.record(12));
+        today.save_results(path).unwrap();
+
+        let mut loaded = Daily::new("2024-03-01".to_string(), 19783);
+        loaded.load_results(path);
+        assert_eq!(loaded.best(), Some(40));
+        assert!(loaded.record(41));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+use rand::Error;
+use rand::Rng;
+use rand::RngCore;
+
+// a small seeded generator for anything that rolls its own dice - actors have
+// to be Clone, Debug and PartialEq, which StdRng isn't
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dice {
+    state: u64
+}
+
+impl Dice {
+    pub fn new(seed: u64) -> Dice {
+        Dice { state: seed }
+    }
+
+    // a fresh set of dice, rolled from another generator
+    pub fn from_rng<R: Rng>(rng: &mut R) -> Dice {
+        Dice::new(rng.gen())
+    }
+}
+
+// splitmix64
+impl RngCore for Dice {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            let n = chunk.len();
+            chunk.copy_from_slice(&bytes[..n]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_rolls_the_same_numbers(){
+        let mut a = Dice::new(7);
+        let mut b = Dice::new(7);
+        let rolls: Vec<u32> = (0..10).map(|_| a.gen_range(0, 1000)).collect();
+
+        assert_eq!(rolls, (0..10).map(|_| b.gen_range(0, 1000)).collect::<Vec<u32>>());
+        assert!(rolls.iter().any(|&r| r != rolls[0]));
+        assert!(Dice::new(8).gen_range(0u32, 1000000) != Dice::new(7).gen_range(0u32, 1000000));
+    }
+}
@@ -0,0 +1,153 @@
+use std::io;
+use spaceship::ShipStats;
+use spaceship_agent::Skill;
+use director::Director;
+use director::SpawnKind;
+use daily::BestScores;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane
+}
+
+// everything a difficulty changes about the AI and what turns up
+pub struct Preset {
+    pub name: &'static str,
+    // ticks an enemy has to be in sight before a pilot reacts to it
+    pub reaction_delay: usize,
+    // how far off, in degrees, a pilot will still open fire
+    pub firing_window: f32,
+    pub aim_error: f32,
+    // ticks between shots for AI ships
+    pub fire_cooldown: i32,
+    pub detection_range: f32,
+    pub spawn_density: f32,
+    pub kamikaze_rate: f32
+}
+
+static PRESETS: &'static [Preset] = &[
+    Preset { name: "easy",   reaction_delay: 45, firing_window: 35.0, aim_error: 12.0, fire_cooldown: 40, detection_range: 1400.0, spawn_density: 0.75, kamikaze_rate: 0.5 },
+    Preset { name: "normal", reaction_delay: 10, firing_window: 20.0, aim_error: 4.0,  fire_cooldown: 20, detection_range: 2000.0, spawn_density: 1.0,  kamikaze_rate: 1.0 },
+    Preset { name: "hard",   reaction_delay: 5,  firing_window: 12.0, aim_error: 2.0,  fire_cooldown: 15, detection_range: 2500.0, spawn_density: 1.25, kamikaze_rate: 1.5 },
+    Preset { name: "insane", reaction_delay: 0,  firing_window: 6.0,  aim_error: 0.0,  fire_cooldown: 10, detection_range: 3000.0, spawn_density: 1.5,  kamikaze_rate: 2.5 }
+];
+
+impl Difficulty {
+    pub fn all() -> Vec<Difficulty> {
+        vec!(Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Insane)
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::all().into_iter().find(|d| d.name() == name)
+    }
+
+    fn index(&self) -> usize {
+        match *self {
+            Difficulty::Easy    => 0,
+            Difficulty::Normal  => 1,
+            Difficulty::Hard    => 2,
+            Difficulty::Insane  => 3
+        }
+    }
+
+    pub fn preset(&self) -> &'static Preset {
+        &PRESETS[self.index()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.preset().name
+    }
+
+    // round and round for the menu
+    pub fn next(&self) -> Difficulty {
+        let all = Difficulty::all();
+        all[(self.index() + 1) % all.len()].clone()
+    }
+
+    pub fn get_skill(&self) -> Skill {
+        let preset = self.preset();
+        Skill {
+            aim_error: preset.aim_error,
+            firing_window: preset.firing_window,
+            reaction_delay: preset.reaction_delay,
+            detection_range: preset.detection_range
+        }
+    }
+
+    pub fn get_stats(&self) -> ShipStats {
+        let mut stats = ShipStats::new();
+        stats.fire_cooldown = self.preset().fire_cooldown;
+        stats
+    }
+
+    pub fn apply(&self, director: &mut Director){
+        let preset = self.preset();
+        director.set_budget_scale(preset.spawn_density);
+        director.boost(&SpawnKind::Kamikaze, preset.kamikaze_rate);
+    }
+}
+
+// the best score on each difficulty, one `difficulty score` per line
+pub struct HighScores {
+    scores: BestScores
+}
+
+impl HighScores {
+    pub fn load(path: &str) -> HighScores {
+        HighScores { scores: BestScores::load(path) }
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        self.scores.save(path)
+    }
+
+    pub fn best(&self, difficulty: &Difficulty) -> usize {
+        self.scores.best(difficulty.name()).unwrap_or(0)
+    }
+
+    // returns true for a new best on this difficulty
+    pub fn record(&mut self, difficulty: &Difficulty, score: usize) -> bool {
+        self.scores.record(difficulty.name(), score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn only_a_better_score_is_a_new_best(){
+        let mut scores = HighScores::load("no such file");
+        assert_eq!(scores.best(&Difficulty::Hard), 0);
+
+        assert!(scores.record(&Difficulty::Hard, 50));
+        assert!(!scores.record(&Difficulty::Hard, 50));
+        assert!(!scores.record(&Difficulty::Hard, 20));
+        assert!(scores.record(&Difficulty::Easy, 10));
+
+        assert_eq!(scores.best(&Difficulty::Hard), 50);
+        assert_eq!(scores.best(&Difficulty::Normal), 0);
+    }
+
+    #[test]
+    fn high_scores_survive_a_save_and_load(){
+        // a file of its own, so other test runs don't trip over it
+        let path = env::temp_dir().join(format!("rusteroids-{}-high_scores_survive_a_save_and_load.scores", process::id()));
+        let path = path.to_str().unwrap();
+        let mut scores = HighScores::load("no such file");
+        scores.record(&Difficulty::Insane, 120);
+        scores.record(&Difficulty::Easy, 7);
+        scores.save(path).unwrap();
+
+        let loaded = HighScores::load(path);
+        assert_eq!(loaded.best(&Difficulty::Insane), 120);
+        assert_eq!(loaded.best(&Difficulty::Easy), 7);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,323 @@
+use std::fs::File;
+use std::io::Read;
+use rand::Rng;
+use actor_manager::ActorManager;
+use asteroid;
+use faction;
+
+static TICKS_PER_MINUTE: usize = 3600;
+static MAX_ATTEMPTS: usize = 20;
+static MIN_SQUADRON: usize = 3;
+static MAX_SQUADRON: usize = 6;
+static DEFAULT_TABLE: &'static str = include_str!("../director.cfg");
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpawnKind {
+    Asteroid,
+    Spaceship,
+    Kamikaze,
+    Minelayer,
+    Squadron,
+    Rival
+}
+
+impl SpawnKind {
+    pub fn all() -> Vec<SpawnKind> {
+        vec!(SpawnKind::Asteroid, SpawnKind::Spaceship, SpawnKind::Kamikaze, SpawnKind::Minelayer, SpawnKind::Squadron, SpawnKind::Rival)
+    }
+
+    fn from_name(name: &str) -> Option<SpawnKind> {
+        match name {
+            "asteroid"  => Some(SpawnKind::Asteroid),
+            "spaceship" => Some(SpawnKind::Spaceship),
+            "kamikaze"  => Some(SpawnKind::Kamikaze),
+            "minelayer" => Some(SpawnKind::Minelayer),
+            "squadron"  => Some(SpawnKind::Squadron),
+            "rival"     => Some(SpawnKind::Rival),
+            _           => None
+        }
+    }
+
+    fn index(&self) -> usize {
+        match *self {
+            SpawnKind::Asteroid     => 0,
+            SpawnKind::Spaceship    => 1,
+            SpawnKind::Kamikaze     => 2,
+            SpawnKind::Minelayer    => 3,
+            SpawnKind::Squadron     => 4,
+            SpawnKind::Rival        => 5
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnRule {
+    pub kind: SpawnKind,
+    pub weight: u32,
+    pub cap: usize,
+    pub per_minute: Option<usize>
+}
+
+// the spawn table to use once the player has collected `tokens`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tier {
+    pub tokens: usize,
+    pub rules: Vec<SpawnRule>
+}
+
+pub struct Director{
+    tiers: Vec<Tier>,
+    ring: (i32, i32),
+    candidates: usize,
+    budget_scale: f32,
+    recent: Vec<(SpawnKind, usize)>,
+    ticks: usize
+}
+
+impl Director{
+    // the table in director.cfg, built in
+    pub fn new() -> Director {
+        Director::parse(DEFAULT_TABLE).unwrap()
+    }
+
+    // falls back to the built in table if the file is missing
+    pub fn load(path: &str) -> Result<Director, String> {
+        let mut contents = String::new();
+
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            Ok(_)   => Director::parse(&contents),
+            Err(_)  => Ok(Director::new())
+        }
+    }
+
+    // `ring min=.. max=.. candidates=..` sets where things appear
+    // `tier tokens=..` starts a table, followed by lines like
+    // `kamikaze weight=3 cap=2 per_minute=3`
+    pub fn parse(contents: &str) -> Result<Director, String> {
+        let mut director = Director {
+            tiers: vec!(),
+            ring: (2600, 4000),
+            candidates: 1,
+            budget_scale: 1.0,
+            recent: vec!(),
+            ticks: 0
+        };
+
+        for (n, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("#") {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or("");
+            let mut values: Vec<(&str, usize)> = vec!();
+            for word in words {
+                let mut parts = word.splitn(2, '=');
+                match (parts.next(), parts.next().and_then(|v| v.parse().ok())) {
+                    (Some(k), Some(v)) => values.push((k, v)),
+                    _                  => return Err(format!("line {}: expected key=number, got '{}'", n + 1, word))
+                }
+            }
+            let get = |key: &str| values.iter().find(|&&(k, _)| k == key).map(|&(_, v)| v);
+
+            match name {
+                "ring" => {
+                    director.ring = (get("min").unwrap_or(2600) as i32, get("max").unwrap_or(4000) as i32);
+                    director.candidates = get("candidates").unwrap_or(1);
+                },
+                "tier" => director.tiers.push(Tier { tokens: get("tokens").unwrap_or(0), rules: vec!() }),
+                _ => match (SpawnKind::from_name(name), director.tiers.last_mut()) {
+                    (Some(kind), Some(tier)) => tier.rules.push(SpawnRule {
+                        kind: kind,
+                        weight: get("weight").unwrap_or(0) as u32,
+                        cap: get("cap").unwrap_or(0),
+                        per_minute: get("per_minute")
+                    }),
+                    (Some(_), None) => return Err(format!("line {}: '{}' comes before any tier", n + 1, name)),
+                    (None, _)       => return Err(format!("line {}: no such kind '{}'", n + 1, name))
+                }
+            }
+        }
+
+        if director.tiers.is_empty() {
+            return Err("no tiers".to_string());
+        }
+        director.tiers.sort_by(|a, b| a.tokens.cmp(&b.tokens));
+
+        Ok(director)
+    }
+
+    // makes a kind `factor` times as common, in every tier
+    pub fn boost(&mut self, kind: &SpawnKind, factor: f32){
+        let scale = |n: usize| (n as f32 * factor).round() as usize;
+        for tier in self.tiers.iter_mut() {
+            for rule in tier.rules.iter_mut().filter(|r| r.kind == *kind) {
+                rule.weight = scale(rule.weight as usize) as u32;
+                rule.cap = scale(rule.cap);
+                rule.per_minute = rule.per_minute.map(|n| scale(n));
+            }
+        }
+    }
+
+    pub fn set_budget_scale(&mut self, scale: f32){
+        self.budget_scale = scale;
+    }
+
+    pub fn restart(&mut self){
+        self.recent = vec!();
+        self.ticks = 0;
+    }
+
+    fn get_tier(&self, tokens: usize) -> Option<&Tier> {
+        self.tiers.iter().filter(|t| t.tokens <= tokens).last()
+    }
+
+    fn recently_spawned(&self, kind: &SpawnKind) -> usize {
+        self.recent.iter().filter(|&&(ref k, _)| k == kind).count()
+    }
+
+    // top the world back up to `budget` things, around the camera
+    // and leaning towards where the player is heading
+    pub fn spawn<R: Rng>(&mut self, actors: &mut ActorManager, (cx, cy): (f32, f32), (vx, vy): (f32, f32),
+                         budget: usize, tokens: usize, rng: &mut R){
+        self.ticks += 1;
+        let ticks = self.ticks;
+        self.recent.retain(|&(_, t)| ticks - t < TICKS_PER_MINUTE);
+
+        let rules = match self.get_tier(tokens) {
+            Some(tier)  => tier.rules.clone(),
+            None        => return
+        };
+
+        let mut counts: Vec<usize> = SpawnKind::all().iter().map(|k| actors.count(k)).collect();
+        let mut total: usize = counts.iter().sum();
+        let budget = (budget as f32 * self.budget_scale) as usize;
+
+        for _ in 0..MAX_ATTEMPTS {
+            if total >= budget {
+                break;
+            }
+
+            let open: Vec<&SpawnRule> = rules.iter().filter(|r| {
+                r.weight > 0
+                    && counts[r.kind.index()] < r.cap
+                    && r.per_minute.map_or(true, |n| self.recently_spawned(&r.kind) < n)
+            }).collect();
+
+            let total_weight: u32 = open.iter().map(|r| r.weight).sum();
+            if total_weight == 0 {
+                break;
+            }
+
+            let mut roll = rng.gen_range(0, total_weight);
+            let rule = open.into_iter().find(|r| {
+                if roll < r.weight {
+                    return true;
+                }
+                roll -= r.weight;
+                false
+            }).unwrap();
+
+            let (x, y) = self.get_position(rng, (cx, cy), (vx, vy));
+            match rule.kind {
+                SpawnKind::Asteroid     => actors.new_asteroid(x, y, random_material(rng)),
+                SpawnKind::Spaceship    => actors.new_spaceship(x, y, random_faction(rng)),
+                SpawnKind::Kamikaze     => actors.new_kamikaze(x, y, (cx, cy)),
+                SpawnKind::Minelayer    => actors.new_minelayer(x, y),
+                SpawnKind::Squadron     => actors.new_squadron(x, y, random_squadron_faction(rng), rng.gen_range(MIN_SQUADRON, MAX_SQUADRON + 1)),
+                SpawnKind::Rival        => actors.new_rival(x, y)
+            }
+
+            counts[rule.kind.index()] += 1;
+            total += 1;
+            self.recent.push((rule.kind.clone(), ticks));
+        }
+    }
+
+    // the best of a few spots in the ring, by how far ahead of the player it is
+    fn get_position<R: Rng>(&self, rng: &mut R, (cx, cy): (f32, f32), (vx, vy): (f32, f32)) -> (i32, i32){
+        let (min, max) = self.ring;
+        let mut best = ActorManager::random_position(rng, (cx, cy), min, max);
+        let ahead = |(x, y): (i32, i32)| (x as f32 - cx) * vx + (y as f32 - cy) * vy;
+
+        for _ in 1..self.candidates {
+            let position = ActorManager::random_position(rng, (cx, cy), min, max);
+            if ahead(position) > ahead(best) {
+                best = position;
+            }
+        }
+
+        best
+    }
+}
+
+fn random_material<R: Rng>(rng: &mut R) -> asteroid::Material {
+    let rand = rng.gen_range(0u32, 100);
+    match rand {
+        0...59  => asteroid::Material::Rock,
+        60...79 => asteroid::Material::Ice,
+        80...91 => asteroid::Material::Metal,
+        _       => asteroid::Material::Volatile
+    }
+}
+
+fn random_faction<R: Rng>(rng: &mut R) -> faction::Faction {
+    let rand = rng.gen_range(0u32, 100);
+    match rand {
+        0...59  => faction::Faction::Pirates,
+        60...79 => faction::Faction::Police,
+        _       => faction::Faction::Traders
+    }
+}
+
+// traders don't fly in squadrons
+fn random_squadron_faction<R: Rng>(rng: &mut R) -> faction::Faction {
+    if rng.gen_range(0u32, 100) < 70 { faction::Faction::Pirates } else { faction::Faction::Police }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_built_in_table_is_director_cfg(){
+        let director = Director::new();
+
+        assert_eq!(director.tiers.iter().map(|t| t.tokens).collect::<Vec<_>>(), vec!(0, 10, 25));
+        assert_eq!(director.ring, (2600, 4000));
+        assert_eq!(director.candidates, 3);
+
+        let tier = director.get_tier(12).unwrap();
+        assert_eq!(tier.tokens, 10);
+        assert!(tier.rules.contains(&SpawnRule { kind: SpawnKind::Squadron, weight: 3, cap: 1, per_minute: Some(1) }));
+        assert!(tier.rules.contains(&SpawnRule { kind: SpawnKind::Rival, weight: 2, cap: 1, per_minute: None }));
+    }
+
+    #[test]
+    fn tiers_are_sorted_by_tokens(){
+        let director = Director::parse("tier tokens=5\nasteroid weight=1 cap=1\ntier tokens=0\nkamikaze weight=1 cap=1").unwrap();
+
+        assert_eq!(director.get_tier(0).unwrap().rules[0].kind, SpawnKind::Kamikaze);
+        assert_eq!(director.get_tier(7).unwrap().rules[0].kind, SpawnKind::Asteroid);
+    }
+
+    #[test]
+    fn mistakes_are_reported_with_their_line(){
+        assert_eq!(Director::parse("tier tokens=0\n\nwhale weight=1").err(), Some("line 3: no such kind 'whale'".to_string()));
+        assert_eq!(Director::parse("asteroid weight=1").err(), Some("line 1: 'asteroid' comes before any tier".to_string()));
+        assert_eq!(Director::parse("tier tokens=lots").err(), Some("line 1: expected key=number, got 'tokens=lots'".to_string()));
+        assert_eq!(Director::parse("# nothing here").err(), Some("no tiers".to_string()));
+    }
+
+    #[test]
+    fn boost_scales_every_tier(){
+        let mut director = Director::new();
+        director.boost(&SpawnKind::Kamikaze, 2.0);
+
+        let caps: Vec<usize> = director.tiers.iter()
+            .flat_map(|t| t.rules.iter().filter(|r| r.kind == SpawnKind::Kamikaze).map(|r| r.cap))
+            .collect();
+        assert_eq!(caps, vec!(4, 8, 12));
+    }
+}
@@ -2,6 +2,7 @@ use actor::Actor;
 use actor::ActorView;
 use actor;
 use messages::GameInstructions;
+use faction::Faction;
 use messages::PlayerInstructions;
 
 static PI : f32 = 3.14159265359;
@@ -17,11 +18,31 @@ pub struct Explosion{
     color: Vec<f32>,
     shape: Vec<f32>,
     size: f32,
-    age: i32
+    age: i32,
+    palette: Vec<Vec<f32>>
 }
 
 impl Explosion{
     pub fn new(x: i32, y: i32, d: i32, r: f32) -> Explosion {
+        let palette = vec!(
+            vec!(0.9, 0.9, 0.9),
+            vec!(0.9, 0.9, 0.4),
+            vec!(0.7, 0.7, 0.7)
+        );
+        Explosion::new_with_palette(x, y, d, r, palette)
+    }
+
+    // the flash a ship leaves behind when it jumps, and arrives in
+    pub fn new_warp(x: i32, y: i32, d: i32) -> Explosion {
+        let palette = vec!(
+            vec!(0.6, 0.8, 1.0),
+            vec!(0.4, 0.5, 0.9),
+            vec!(0.3, 0.3, 0.6)
+        );
+        Explosion::new_with_palette(x, y, d, 0.0, palette)
+    }
+
+    fn new_with_palette(x: i32, y: i32, d: i32, r: f32, palette: Vec<Vec<f32>>) -> Explosion {
 
         let size = d as f32 / 2000.0;
         let shape = vec!(
@@ -33,7 +54,7 @@ impl Explosion{
             0.0,  size
         );
 
-        let color = vec!(0.9, 0.9, 0.9);
+        let color = palette[0].clone();
         Explosion{
             id: 0, x: x as f32, y: y as f32,
             rotation: r,
@@ -41,7 +62,8 @@ impl Explosion{
             color: color,
             shape: shape,
             age: 0,
-            size: size
+            size: size,
+            palette: palette
         }
     }
 }
@@ -54,7 +76,7 @@ impl Actor for Explosion{
 
         if self.age > 15 {
             let s = self.size * 1.5;
-            self.color = vec!(0.7, 0.7, 0.7);
+            self.color = self.palette[2].clone();
             self.shape = vec!(
                 0.0,  s,
                 s, 0.0,
@@ -68,7 +90,7 @@ impl Actor for Explosion{
 
         }else if self.age > 10 {
             let s = self.size * 2.0;
-            self.color = vec!(0.9, 0.9, 0.4);
+            self.color = self.palette[1].clone();
             self.shape = vec!(
                 -s,  s,
                 -s,   -s,
@@ -92,8 +114,11 @@ impl Actor for Explosion{
         ActorView {
             id: self.id,
             parent: 0,
+            faction: Faction::Unaligned,
             x: self.x,
             y: self.y,
+            vx: 0.0,
+            vy: 0.0,
             width: 0.0,
             height: 0.0,
             rotation: self.rotation * PI / 180.0,
@@ -103,7 +128,10 @@ impl Actor for Explosion{
             show_secondary: false,
             secondary_shape: None,
             secondary_color: None,
-            meter: 0.0
+            meter: 0.0,
+            cooldown: 0.0,
+            damage: 0.0,
+            hull: 1.0
         }
     }
 
@@ -0,0 +1,93 @@
+#[derive(Clone, Debug, PartialEq)]
+pub enum Faction {
+    Unaligned,
+    Player,
+    Pirates,
+    Police,
+    Traders,
+    // out for the token, and only the player stands in their way
+    Rivals
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Relationship {
+    Friendly,
+    Neutral,
+    Hostile
+}
+
+impl Faction {
+    pub fn relationship(&self, other: &Faction) -> Relationship {
+        if self == other {
+            return match *self {
+                // rocks still smash into each other
+                Faction::Unaligned => Relationship::Neutral,
+                _                  => Relationship::Friendly
+            };
+        }
+
+        match (self, other) {
+            (&Faction::Unaligned, _) | (_, &Faction::Unaligned)     => Relationship::Neutral,
+            (&Faction::Player, &Faction::Police)
+            | (&Faction::Police, &Faction::Player)                  => Relationship::Friendly,
+            (&Faction::Police, &Faction::Traders)
+            | (&Faction::Traders, &Faction::Police)                 => Relationship::Friendly,
+            (&Faction::Player, &Faction::Traders)
+            | (&Faction::Traders, &Faction::Player)                 => Relationship::Neutral,
+            (&Faction::Player, &Faction::Rivals)
+            | (&Faction::Rivals, &Faction::Player)                  => Relationship::Hostile,
+            (&Faction::Rivals, _) | (_, &Faction::Rivals)           => Relationship::Neutral,
+            (&Faction::Pirates, _) | (_, &Faction::Pirates)         => Relationship::Hostile,
+            _                                                       => Relationship::Neutral
+        }
+    }
+
+    pub fn is_hostile(&self, other: &Faction) -> bool {
+        self.relationship(other) == Relationship::Hostile
+    }
+
+    // friends don't collide with, shoot or blow each other up
+    pub fn is_friendly(&self, other: &Faction) -> bool {
+        self.relationship(other) == Relationship::Friendly
+    }
+
+    pub fn color(&self) -> Vec<f32> {
+        match *self {
+            Faction::Player     => vec!(0.7, 0.7, 0.77),
+            Faction::Police     => vec!(0.2, 0.4, 0.8),
+            Faction::Traders    => vec!(0.3, 0.6, 0.4),
+            Faction::Rivals     => vec!(0.6, 0.3, 0.8),
+            _                   => vec!(0.5, 0.2, 0.2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_relationship_table(){
+        use self::Relationship::{Friendly as F, Neutral as N, Hostile as H};
+        let factions = vec!(Faction::Unaligned, Faction::Player, Faction::Pirates, Faction::Police, Faction::Traders, Faction::Rivals);
+        let table = vec!(
+            //     unaligned player pirates police traders rivals
+            vec!(N,        N,     N,      N,     N,      N),   // unaligned
+            vec!(N,        F,     H,      F,     N,      H),   // player
+            vec!(N,        H,     F,      H,     H,      N),   // pirates
+            vec!(N,        F,     H,      F,     F,      N),   // police
+            vec!(N,        N,     H,      F,     F,      N),   // traders
+            vec!(N,        H,     N,      N,     N,      F)    // rivals
+        );
+
+        for (a, row) in factions.iter().zip(table.iter()) {
+            for (b, relationship) in factions.iter().zip(row.iter()) {
+                assert_eq!(&a.relationship(b), relationship, "{:?} to {:?}", a, b);
+                assert_eq!(a.relationship(b), b.relationship(a), "{:?} and {:?} disagree", a, b);
+            }
+        }
+
+        assert!(Faction::Rivals.is_hostile(&Faction::Player) && !Faction::Rivals.is_hostile(&Faction::Rivals));
+        assert!(!Faction::Traders.is_hostile(&Faction::Player) && !Faction::Traders.is_friendly(&Faction::Player));
+    }
+}
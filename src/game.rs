@@ -1,36 +1,167 @@
 use actor;
 use messages::GameInstructions;
+use loot::Loot;
+use faction::Faction;
 static MAX_PLAYERS: usize = 5;
+static BOSS_EVERY: usize = 5;
+static TOKEN_POINTS: usize = 10;
+static KILL_POINTS: usize = 1;
+static COMBO_WINDOW: usize = 180;
+static COMBO_STEP: usize = 3;
+static MAX_MULTIPLIER: usize = 8;
+static TOKEN_CREDITS: usize = 5;
+static KILL_CREDITS: usize = 2;
 
 pub struct Game{
     pub score: usize,
-    pub highscore: usize
+    pub highscore: usize,
+    pub tokens: usize,
+    pub kills: usize,
+    // tokens a rival got to first
+    pub rival_tokens: usize,
+    loot_credits: usize,
+    token_points: usize,
+    kill_points: usize,
+    combo: usize,
+    combo_timer: usize,
+    best_multiplier: usize,
+    next_boss: usize
 }
 
 impl Game{
     pub fn new() -> Game {
         Game {
             score : 0,
-            highscore: 0
+            highscore: 0,
+            tokens: 0,
+            kills: 0,
+            rival_tokens: 0,
+            loot_credits: 0,
+            token_points: 0,
+            kill_points: 0,
+            combo: 0,
+            combo_timer: 0,
+            best_multiplier: 1,
+            next_boss: BOSS_EVERY
         }
     }
     pub fn max_players(&self)-> usize{
-        MAX_PLAYERS + (self.score * 3)
+        MAX_PLAYERS + (self.tokens * 3)
     }
 
     pub fn restart(&mut self){
         self.score = 0;
+        self.tokens = 0;
+        self.kills = 0;
+        self.rival_tokens = 0;
+        self.loot_credits = 0;
+        self.token_points = 0;
+        self.kill_points = 0;
+        self.best_multiplier = 1;
+        self.break_combo();
+        self.next_boss = BOSS_EVERY;
+    }
+
+    // holds off while a boss is still out there, so the next one follows it
+    pub fn boss_due(&mut self, boss_alive: bool) -> bool {
+        if boss_alive || self.tokens < self.next_boss {
+            return false;
+        }
+
+        // any thresholds passed while waiting don't stack up
+        self.next_boss = (self.tokens / BOSS_EVERY + 1) * BOSS_EVERY;
+        true
+    }
+
+    pub fn multiplier(&self) -> usize {
+        let m = 1 + self.combo / COMBO_STEP;
+        if m > MAX_MULTIPLIER { MAX_MULTIPLIER } else { m }
+    }
+
+    // how much of the combo window is left, from 1.0 down to 0.0
+    pub fn combo_meter(&self) -> f32 {
+        self.combo_timer as f32 / COMBO_WINDOW as f32
+    }
+
+    // what this run is worth towards upgrades
+    pub fn credits(&self) -> usize {
+        self.tokens * TOKEN_CREDITS + self.kills * KILL_CREDITS + self.loot_credits
+    }
+
+    // how much of the score came from tokens and how much from kills
+    pub fn breakdown(&self) -> (f32, f32) {
+        if self.score == 0 {
+            return (0.0, 0.0);
+        }
+        (self.token_points as f32 / self.score as f32, self.kill_points as f32 / self.score as f32)
+    }
+
+    pub fn best_multiplier(&self) -> usize {
+        self.best_multiplier
+    }
+
+    pub fn summary(&self) -> String {
+        format!("game over - score [{}] = tokens {} x{} [{}] + kills {} [{}] - lost to rivals {} - best multiplier x{} - credits +{}",
+                self.score, self.tokens, TOKEN_POINTS, self.token_points,
+                self.kills, self.kill_points, self.rival_tokens, self.best_multiplier, self.credits())
+    }
+
+    pub fn tick(&mut self){
+        if self.combo_timer > 0 {
+            self.combo_timer -= 1;
+            if self.combo_timer == 0 {
+                self.break_combo();
+            }
+        }
+    }
+
+    fn break_combo(&mut self){
+        self.combo = 0;
+        self.combo_timer = 0;
+    }
+
+    fn add_points(&mut self, points: usize) -> usize {
+        let multiplier = self.multiplier();
+        let total = points * multiplier;
+
+        self.score += total;
+        if self.highscore < self.score {
+            self.highscore = self.score;
+        }
+
+        if self.best_multiplier < multiplier {
+            self.best_multiplier = multiplier;
+        }
+
+        self.combo += 1;
+        self.combo_timer = COMBO_WINDOW;
+        total
     }
 
     pub fn process_messages(&mut self, messages: Vec<(GameInstructions, actor::ActorView)>){
         for &(ref msg, ref v) in messages.iter(){
             match msg{
-                &GameInstructions::Collect  => {
+                &GameInstructions::Collect(_) => {
                     if v.id == 1 {
-                        self.score += 1;
-                        if self.highscore < self.score {
-                            self.highscore = self.score;
-                        }
+                        self.tokens += 1;
+                        self.token_points += self.add_points(TOKEN_POINTS);
+                    } else if v.faction == Faction::Rivals {
+                        // that point's gone
+                        self.rival_tokens += 1;
+                    }
+                },
+                &GameInstructions::Kill(killer) => {
+                    if killer == 1 {
+                        self.kills += 1;
+                        self.kill_points += self.add_points(KILL_POINTS);
+                    }
+                },
+                &GameInstructions::Loot(Loot::Credits(credits), 1) => self.loot_credits += credits,
+                // any hit that gets through costs the combo, not just the last one
+                &GameInstructions::Hit
+                | &GameInstructions::Explode  => {
+                    if v.id == 1 {
+                        self.break_combo();
                     }
                 },
                 _       => ()
@@ -38,3 +169,63 @@ impl Game{
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spaceship::Spaceship;
+    use actor::Actor;
+
+    #[test]
+    fn a_hit_that_doesnt_kill_still_breaks_the_combo(){
+        let mut game = Game::new();
+        let player = Spaceship::new(1, 0, 0, 0.0).get_view();
+        for _ in 0..COMBO_STEP {
+            game.process_messages(vec!((GameInstructions::Collect(5), player.clone())));
+        }
+        assert_eq!(game.multiplier(), 2);
+
+        game.process_messages(vec!((GameInstructions::Hit, player.clone())));
+        assert_eq!(game.multiplier(), 1);
+    }
+
+    #[test]
+    fn other_ships_getting_hit_leave_the_combo_alone(){
+        let mut game = Game::new();
+        let player = Spaceship::new(1, 0, 0, 0.0).get_view();
+        let other = Spaceship::new(2, 0, 0, 0.0).get_view();
+        for _ in 0..COMBO_STEP {
+            game.process_messages(vec!((GameInstructions::Collect(5), player.clone())));
+        }
+
+        game.process_messages(vec!((GameInstructions::Hit, other)));
+        assert_eq!(game.multiplier(), 2);
+    }
+
+    #[test]
+    fn no_second_boss_while_the_first_is_alive(){
+        let mut game = Game::new();
+        game.tokens = BOSS_EVERY;
+        assert!(!game.boss_due(true));
+
+        game.tokens = BOSS_EVERY * 2;
+        assert!(!game.boss_due(true));
+        assert!(game.boss_due(false));
+        assert!(!game.boss_due(false));
+    }
+
+    #[test]
+    fn the_breakdown_adds_up_to_the_score(){
+        let mut game = Game::new();
+        assert_eq!(game.breakdown(), (0.0, 0.0));
+
+        let player = Spaceship::new(1, 0, 0, 0.0).get_view();
+        game.process_messages(vec!((GameInstructions::Collect(5), player.clone()), (GameInstructions::Kill(1), player.clone())));
+        let (tokens, kills) = game.breakdown();
+
+        assert_eq!(game.score, TOKEN_POINTS + KILL_POINTS);
+        assert!((tokens + kills - 1.0).abs() < 0.001);
+        assert!(tokens > kills);
+        assert_eq!(game.best_multiplier(), 1);
+    }
+}
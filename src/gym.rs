@@ -0,0 +1,277 @@
+use actor::ActorView;
+use actor_manager::ActorManager;
+use collisions;
+use director::Director;
+use game::Game;
+use messages::PlayerInstructions;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+static OBSERVATION_RANGE: f32 = 2000.0;
+static MAX_STEPS: usize = 18000;
+
+// what the ship wants to do this step - the keys it's holding down
+#[derive(Clone, Debug, PartialEq)]
+pub struct Action {
+    pub thrust: bool,
+    pub reverse: bool,
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+    pub shield: bool,
+    pub mine: bool,
+    pub hyperspace: bool,
+    pub tractor: bool
+}
+
+impl Action {
+    pub fn new() -> Action {
+        Action { thrust: false, reverse: false, left: false, right: false, fire: false, shield: false, mine: false,
+                 hyperspace: false, tractor: false }
+    }
+
+    pub fn to_instructions(&self) -> Vec<PlayerInstructions> {
+        let mut instructions = vec!(
+            if self.thrust  { PlayerInstructions::BeginIncreaseThrottle } else { PlayerInstructions::StopIncreaseThrottle },
+            if self.reverse { PlayerInstructions::BeginDecreaseThrottle } else { PlayerInstructions::StopDecreaseThrottle },
+            if self.left    { PlayerInstructions::BeginRotateLeft } else { PlayerInstructions::StopRotateLeft },
+            if self.right   { PlayerInstructions::BeginRotateRight } else { PlayerInstructions::StopRotateRight },
+            if self.shield  { PlayerInstructions::ShieldUp } else { PlayerInstructions::ShieldDown }
+        );
+
+        if self.fire {
+            instructions.push(PlayerInstructions::Fire);
+        }
+        if self.mine {
+            instructions.push(PlayerInstructions::DropMine);
+        }
+        if self.hyperspace {
+            instructions.push(PlayerInstructions::Hyperspace);
+        }
+        if self.tractor {
+            instructions.push(PlayerInstructions::Tractor);
+        }
+        instructions
+    }
+}
+
+// what each event is worth
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rewards {
+    pub token: f32,
+    pub kill: f32,
+    // every step the ship is still flying
+    pub survival: f32,
+    pub death: f32
+}
+
+impl Rewards {
+    pub fn new() -> Rewards {
+        Rewards { token: 1.0, kill: 0.2, survival: 0.001, death: -1.0 }
+    }
+}
+
+// the world as the ship sees it - positions and velocities are relative to the
+// ship and turned so its nose points along +y
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    // nearest first
+    pub nearby: Vec<ActorView>,
+    pub velocity: (f32, f32),
+    // degrees off the nose, and how far
+    pub token: Option<(f32, f32)>,
+    pub shield: f32,
+    pub hull: f32
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Info {
+    pub steps: usize,
+    pub score: usize,
+    pub tokens: usize,
+    pub kills: usize
+}
+
+// a headless, gym-style wrapper round the game for training pilots -
+// no window, no files and no clock, just reset and step
+pub struct Environment {
+    actors: ActorManager,
+    director: Director,
+    game: Game,
+    rng: StdRng,
+    rewards: Rewards,
+    max_steps: usize,
+    steps: usize,
+    // once the episode's over, where it ended
+    done: Option<Observation>
+}
+
+impl Environment {
+    pub fn new(rewards: Rewards) -> Environment {
+        Environment {
+            actors: ActorManager::new(),
+            director: Director::new(),
+            game: Game::new(),
+            rng: StdRng::seed_from_u64(0),
+            rewards: rewards,
+            max_steps: MAX_STEPS,
+            steps: 0,
+            done: None
+        }
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: usize){
+        self.max_steps = max_steps;
+    }
+
+    // the same seed and the same actions always play out the same way
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.actors = ActorManager::new();
+        self.actors.set_seed(Some(seed));
+        self.actors.restart();
+        self.director = Director::new();
+        self.game = Game::new();
+        self.rng = StdRng::seed_from_u64(seed);
+        self.steps = 0;
+        self.done = None;
+        self.observe().unwrap()
+    }
+
+    // stepping on after the episode's over changes nothing and earns nothing
+    pub fn step(&mut self, action: &Action) -> (Observation, f32, bool, Info) {
+        if let Some(ref observation) = self.done {
+            return (observation.clone(), 0.0, true, self.info());
+        }
+
+        let mut messages: Vec<(i32, PlayerInstructions)> = action.to_instructions().into_iter().map(|i| (1, i)).collect();
+        collisions::calculate_collisions(&self.actors, &mut messages);
+
+        let mut output_messages = vec!();
+        self.actors.update(messages, &mut output_messages);
+        self.actors.process_messages(&mut output_messages);
+
+        let (tokens, kills) = (self.game.tokens, self.game.kills);
+        self.game.process_messages(output_messages);
+        self.game.tick();
+        self.steps += 1;
+
+        let mut reward = (self.game.tokens - tokens) as f32 * self.rewards.token
+                       + (self.game.kills - kills) as f32 * self.rewards.kill;
+
+        let (observation, done) = match self.observe() {
+            Some(observation) => {
+                reward += self.rewards.survival;
+                let player = self.actors.get_player().unwrap();
+                self.director.spawn(&mut self.actors, (player.x, player.y), (player.vx, player.vy),
+                                    self.game.max_players(), self.game.tokens, &mut self.rng);
+                (observation, self.steps >= self.max_steps)
+            },
+            None => {
+                reward += self.rewards.death;
+                (Observation { nearby: vec!(), velocity: (0.0, 0.0), token: None, shield: 0.0, hull: 0.0 }, true)
+            }
+        };
+
+        if done {
+            self.done = Some(observation.clone());
+        }
+        (observation, reward, done, self.info())
+    }
+
+    fn info(&self) -> Info {
+        Info { steps: self.steps, score: self.game.score, tokens: self.game.tokens, kills: self.game.kills }
+    }
+
+    // None once the ship's gone
+    fn observe(&self) -> Option<Observation> {
+        let player = match self.actors.get_player() {
+            Some(player) => player,
+            None         => return None
+        };
+
+        let mut nearby: Vec<ActorView> = self.actors.get_within(player.x, player.y, OBSERVATION_RANGE).into_iter()
+            .filter(|a| a.id != 1)
+            .map(|a| egocentric(&player, a))
+            .collect();
+        nearby.sort_by(|a, b| (a.x * a.x + a.y * a.y).partial_cmp(&(b.x * b.x + b.y * b.y)).unwrap());
+
+        let token = self.actors.get_collectables().into_iter().next().map(|t| {
+            let t = egocentric(&player, t);
+            (t.x.atan2(t.y) * 180.0 / 3.14159265359, (t.x * t.x + t.y * t.y).sqrt())
+        });
+
+        Some(Observation {
+            nearby: nearby,
+            velocity: rotate(&player, player.vx, player.vy),
+            token: token,
+            shield: player.meter,
+            hull: player.hull
+        })
+    }
+}
+
+// into the ship's frame - along the nose is (sin r, cos r), across it is (cos r, -sin r)
+fn rotate(ship: &ActorView, x: f32, y: f32) -> (f32, f32) {
+    (x * ship.rotation.cos() - y * ship.rotation.sin(), x * ship.rotation.sin() + y * ship.rotation.cos())
+}
+
+fn egocentric(ship: &ActorView, mut other: ActorView) -> ActorView {
+    let (x, y) = rotate(ship, other.x - ship.x, other.y - ship.y);
+    let (vx, vy) = rotate(ship, other.vx - ship.vx, other.vy - ship.vy);
+    other.x = x;
+    other.y = y;
+    other.vx = vx;
+    other.vy = vy;
+    other.rotation -= ship.rotation;
+    other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(seed: u64) -> Vec<(Observation, f32, bool, Info)> {
+        let mut env = Environment::new(Rewards::new());
+        env.reset(seed);
+        let mut action = Action::new();
+        let mut trajectory = vec!();
+
+        for i in 0..900 {
+            action.thrust = i % 90 < 60;
+            action.left = i % 200 < 30;
+            action.fire = i % 10 == 0;
+            action.mine = i % 150 == 0;
+            action.tractor = i % 120 == 0;
+            action.hyperspace = i == 450;
+
+            let step = env.step(&action);
+            let done = step.2;
+            trajectory.push(step);
+            if done {
+                break;
+            }
+        }
+        trajectory
+    }
+
+    #[test]
+    fn stepping_past_the_end_changes_nothing(){
+        let mut env = Environment::new(Rewards::new());
+        env.reset(3);
+        env.set_max_steps(5);
+        let action = Action::new();
+        for _ in 0..4 {
+            assert!(!env.step(&action).2);
+        }
+        let (observation, _, done, info) = env.step(&action);
+        assert!(done);
+
+        assert_eq!(env.step(&action), (observation, 0.0, true, info));
+    }
+
+    #[test]
+    fn the_same_seed_flies_the_same_run(){
+        assert_eq!(run(3), run(3));
+        assert!(run(3) != run(4));
+    }
+}
@@ -5,8 +5,19 @@ use actor;
 
 use messages::PlayerInstructions;
 use messages::GameInstructions;
+use faction::Faction;
+use loot::LootTable;
+use rand::Rng;
+use dice::Dice;
 
 static PI : f32 = 3.14159265359;
+static TURN_RATE: f32 = 2.5;
+static MAX_SPEED: f32 = 70.0;
+static FUEL: usize = 600;
+static DRIFT_TIME: usize = 120;
+static MISS_CHANCE: u32 = 35; // percent
+static WARNING_RANGE: f32 = 2600.0;
+static WARNING_TIME: usize = 90;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Kamikaze{
@@ -19,17 +30,36 @@ pub struct Kamikaze{
     shape: Vec<f32>,
     acc: f32,
     is_alive:bool,
-    color: Vec<f32>
+    color: Vec<f32>,
+    target: (f32, f32),
+    closest: f32,
+    dice: Dice,
+    fuel: usize,
+    drift: usize,
+    warning: usize,
+    warned: bool,
+    warning_shape: Vec<f32>
 }
 
 impl Kamikaze{
-    pub fn new(id: i32, x: i32, y: i32, (target_x, target_y): (f32, f32)) -> Kamikaze {
+    pub fn new(id: i32, x: i32, y: i32, (target_x, target_y): (f32, f32), dice: Dice) -> Kamikaze {
         let shape = vec!(
             0.0,  0.06,
             0.024, -0.06,
             -0.024, -0.06
         );
 
+        // a chevron out in front - flashes as it comes into range
+        let warning_shape = vec!(
+            0.0, 0.16,
+            0.05, 0.1,
+            0.0, 0.13,
+
+            0.0, 0.13,
+            -0.05, 0.1,
+            0.0, 0.16
+        );
+
         let color = vec!(0.15, 0.15, 0.5);
         let acc = 1.01;
         let dx = target_x - x as f32;
@@ -42,57 +72,172 @@ impl Kamikaze{
             shape: shape,
             acc: acc,
             is_alive: true,
-            color: color
+            color: color,
+            target: (target_x, target_y),
+            closest: -1.0,
+            dice: dice,
+            fuel: FUEL,
+            drift: 0,
+            warning: 0,
+            warned: false,
+            warning_shape: warning_shape
+        }
+    }
+
+    pub fn track(&mut self, target: (f32, f32)){
+        self.target = target;
+    }
+
+    fn target_distance(&self) -> f32 {
+        let (tx, ty) = self.target;
+        let dx = tx - self.x;
+        let dy = ty - self.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    // how far off the nose the target is, in degrees
+    fn target_angle(&self) -> f32 {
+        let (tx, ty) = self.target;
+        let ideal_rotation = (tx - self.x).atan2(ty - self.y) * 180.0 / PI;
+        let mut d_rotation = ideal_rotation - self.rotation;
+
+        while d_rotation > 180.0 {
+            d_rotation -= 360.0;
+        }
+
+        while d_rotation < -180.0 {
+            d_rotation += 360.0;
         }
+
+        d_rotation
     }
 
+    fn turn_to_target(&mut self){
+        let d_rotation = self.target_angle();
+
+        if d_rotation > TURN_RATE {
+            self.rotation += TURN_RATE;
+        } else if d_rotation < -TURN_RATE {
+            self.rotation -= TURN_RATE;
+        } else {
+            self.rotation += d_rotation;
+        }
+    }
 
     fn accelerate(&mut self){
         let acc = self.acc;
         let (dirx, diry) = self.get_rotate_vec();
         self.acc_x += acc * dirx;
         self.acc_y += acc * diry;
+
+        let speed = (self.acc_x * self.acc_x + self.acc_y * self.acc_y).sqrt();
+        if speed > MAX_SPEED {
+            self.acc_x *= MAX_SPEED / speed;
+            self.acc_y *= MAX_SPEED / speed;
+        }
     }
 
     fn get_rotate_vec(&mut self) -> (f32, f32){
         let r = (self.rotation * PI) / 180.0;
         (r.sin(), r.cos())
     }
+
+    // overshooting the target sometimes burns the engine out
+    fn check_miss(&mut self){
+        let distance = self.target_distance();
+
+        if self.closest < 0.0 || distance < self.closest {
+            self.closest = distance;
+            return;
+        }
+
+        if self.closest < WARNING_RANGE / 2.0 && self.target_angle().abs() > 90.0 {
+            self.closest = -1.0;
+            if self.dice.gen_range(0u32, 100) < MISS_CHANCE {
+                self.flame_out();
+            }
+        }
+    }
+
+    fn flame_out(&mut self){
+        self.fuel = 0;
+        self.color = vec!(0.3, 0.3, 0.35);
+    }
+
+    fn warn(&mut self){
+        if !self.warned && self.target_distance() < WARNING_RANGE {
+            self.warned = true;
+            self.warning = WARNING_TIME;
+        }
+
+        if self.warning > 0 {
+            self.warning -= 1;
+        }
+    }
 }
 
 
 impl Actor for Kamikaze{
 
-    fn update(&mut self, _:&mut Vec<(GameInstructions, ActorView)>){
-        self.accelerate();
+    fn update(&mut self, output_messages:&mut Vec<(GameInstructions, ActorView)>){
         self.y += self.acc_y;
         self.x += self.acc_x;
+
+        if self.fuel == 0 {
+            // engine's gone - drift for a while then fizzle out
+            self.acc_x *= 0.98;
+            self.acc_y *= 0.98;
+            self.drift += 1;
+            if self.drift > DRIFT_TIME {
+                self.is_alive = false;
+                output_messages.push((GameInstructions::Explode, self.get_view().clone()));
+            }
+            return;
+        }
+
+        self.fuel -= 1;
+        if self.fuel == 0 {
+            self.flame_out();
+        }
+
+        self.warn();
+        self.turn_to_target();
+        self.accelerate();
+        self.check_miss();
     }
 
     fn get_view(&self) -> ActorView {
         ActorView {
             id: self.id,
             parent: 0,
+            faction: Faction::Pirates,
             x: self.x,
             y: self.y,
+            vx: self.acc_x,
+            vy: self.acc_y,
             width: 50.0,
             height: 100.0,
             rotation: (self.rotation * PI) / 180.0,
             shape: self.shape.clone(),
             color: self.color.clone(),
             collision_type: actor::CollisionType::Collide,
-            show_secondary: false,
-            secondary_shape: None,
-            secondary_color: None,
-            meter: 0.0
+            show_secondary: self.warning > 0 && (self.warning / 8) % 2 == 0,
+            secondary_shape: Some(self.warning_shape.clone()),
+            secondary_color: Some(vec!(1.0, 0.3, 0.2)),
+            meter: 0.0,
+            cooldown: 0.0,
+            damage: 60.0,
+            hull: 1.0
         }
     }
 
     fn execute(&mut self, message: &PlayerInstructions, output_messages:&mut Vec<(GameInstructions, ActorView)>){
         match message {
-            &PlayerInstructions::Collide => {
+            &PlayerInstructions::Collide(_)
+            | &PlayerInstructions::Deflect(..) => {
                                             self.is_alive = false;
                                             output_messages.push((GameInstructions::Explode, self.get_view().clone()));
+                                            output_messages.push((GameInstructions::DropLoot(LootTable::Kamikaze), self.get_view().clone()));
                                         },
             _                           => ()
         };
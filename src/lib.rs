@@ -0,0 +1,29 @@
+extern crate rand;
+
+pub mod actor;
+pub mod actor_manager;
+pub mod spaceship;
+pub mod spaceship_agent;
+pub mod agent;
+pub mod squadron;
+pub mod bullet;
+pub mod asteroid;
+pub mod kamikaze;
+pub mod explosion;
+pub mod token;
+pub mod mine;
+pub mod mothership;
+pub mod race;
+pub mod faction;
+pub mod profile;
+pub mod achievements;
+pub mod daily;
+pub mod difficulty;
+pub mod director;
+pub mod loot;
+pub mod pickup;
+pub mod game;
+pub mod collisions;
+pub mod gym;
+pub mod messages;
+pub mod dice;
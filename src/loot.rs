@@ -0,0 +1,70 @@
+use rand::Rng;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Loot {
+    Credits(usize),
+    Ammo,
+    ShieldCharge,
+    WeaponPart
+}
+
+impl Loot {
+    pub fn color(&self) -> Vec<f32> {
+        match *self {
+            Loot::Credits(_)    => vec!(0.95, 0.75, 0.2),
+            Loot::Ammo          => vec!(0.9, 0.45, 0.2),
+            Loot::ShieldCharge  => vec!(0.4, 0.85, 0.7),
+            Loot::WeaponPart    => vec!(0.75, 0.75, 0.85)
+        }
+    }
+}
+
+// who dropped it decides what's in it
+#[derive(Clone, Debug, PartialEq)]
+pub enum LootTable {
+    Pirate,
+    Police,
+    Trader,
+    Kamikaze
+}
+
+// each entry is rolled on its own - percent chance of it dropping
+static PIRATE: &'static [(Loot, u32)] = &[
+    (Loot::Credits(5), 60),
+    (Loot::Ammo, 30),
+    (Loot::WeaponPart, 15)
+];
+
+static POLICE: &'static [(Loot, u32)] = &[
+    (Loot::Credits(3), 40),
+    (Loot::ShieldCharge, 50)
+];
+
+static TRADER: &'static [(Loot, u32)] = &[
+    (Loot::Credits(10), 90),
+    (Loot::Credits(10), 50),
+    (Loot::ShieldCharge, 20)
+];
+
+static KAMIKAZE: &'static [(Loot, u32)] = &[
+    (Loot::Credits(2), 35),
+    (Loot::Ammo, 15)
+];
+
+impl LootTable {
+    fn drops(&self) -> &'static [(Loot, u32)] {
+        match *self {
+            LootTable::Pirate   => PIRATE,
+            LootTable::Police   => POLICE,
+            LootTable::Trader   => TRADER,
+            LootTable::Kamikaze => KAMIKAZE
+        }
+    }
+
+    pub fn roll<R: Rng>(&self, rng: &mut R) -> Vec<Loot> {
+        self.drops().iter()
+            .filter(|&&(_, chance)| rng.gen_range(0u32, 100) < chance)
+            .map(|&(ref loot, _)| loot.clone())
+            .collect()
+    }
+}
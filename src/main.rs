@@ -3,26 +3,43 @@ extern crate glfw;
 extern crate time;
 extern crate rand;
 
+extern crate rusteroids;
+
 use gl::types::*;
 use glfw::Context;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use std::mem;
 use std::ptr;
 use std::str;
 use std::iter::repeat;
+use std::env;
+
+use rusteroids::actor;
+use rusteroids::actor_manager;
+use rusteroids::agent;
+use rusteroids::race;
+use rusteroids::faction;
+use rusteroids::profile;
+use rusteroids::achievements;
+use rusteroids::daily;
+use rusteroids::difficulty;
+use rusteroids::director;
+use rusteroids::game;
+use rusteroids::messages;
+use rusteroids::collisions;
+use rusteroids::spaceship_agent;
 
-mod actor;
-mod actor_manager;
-mod spaceship;
-mod spaceship_agent;
-mod bullet;
-mod asteroid;
-mod kamikaze;
-mod explosion;
-mod token;
-mod game;
 mod background;
-mod messages;
+
+static RACE_CHECKPOINTS: usize = 10;
+static PROFILE_PATH: &'static str = "rusteroids.profile";
+static ACHIEVEMENTS_PATH: &'static str = "rusteroids.achievements";
+static DAILY_PATH: &'static str = "rusteroids.daily";
+static DIRECTOR_PATH: &'static str = "director.cfg";
+static PILOTS_PATH: &'static str = "pilots.cfg";
+static SCORES_PATH: &'static str = "rusteroids.scores";
 
 // Shader sources
 // vertex shader
@@ -174,22 +191,82 @@ fn main() {
     let mut cam_pos = (0.0, 0.0);
     let mut game = game::Game::new();
 
-    let mut reset_countdown:usize = 3;
+    let mut race = get_race();
+    let mut daily = if race.is_none() { get_daily() } else { None };
+
+    // spawns and stars come from here so a daily run can replay the same world
+    let mut world = StdRng::from_entropy();
+    let mut director = load_director();
     let mut actors = actor_manager::ActorManager::new();
-    actors.restart();
+    actors.set_trees(load_trees());
+
+    // the daily challenge is the same for everyone, so it's always on normal
+    let mut difficulty = if daily.is_some() { difficulty::Difficulty::Normal } else { get_difficulty() };
+    let mut scores = difficulty::HighScores::load(SCORES_PATH);
+    set_difficulty(&difficulty, &mut director, &mut actors, &mut game, &scores);
+    match daily {
+        Some(ref d) => d.apply(&mut director),
+        None        => ()
+    }
+
+    // credits and upgrades carry over between runs
+    let mut profile = profile::Profile::load(PROFILE_PATH);
+    let mut upgrading = false;
+    let mut achievements = achievements::Achievements::load(ACHIEVEMENTS_PATH);
+
+    let mut reset_countdown:usize = 3;
+    actors.set_player_stats(profile.get_stats());
+    restart(&mut actors, &mut game, &mut race, &mut world);
 
     let mut background = background::Background::new();
     background.generate(cam_pos.clone());
 
+    match daily {
+        Some(ref d) => start_daily(d, &mut actors, &mut world, &mut background),
+        None        => ()
+    }
+
     while !window.should_close() {
 
         // Poll events
         glfw.poll_events();
 
         let mut messages = vec!();
+        let mut menu = vec!();
 
         for event in glfw::flush_messages(&events) {
-            handle_window_event(&mut window, event, &mut messages);
+            handle_window_event(&mut window, event, &mut messages, &mut menu);
+        }
+
+        if menu.contains(&glfw::Key::P) {
+            toggle_autopilot(&mut actors, &mut messages);
+        }
+
+        if menu.contains(&glfw::Key::R) {
+            watch_replay(&mut actors, &mut game, &mut race, &mut world, &mut director);
+        }
+
+        if upgrading {
+            for key in menu.into_iter() {
+                match key {
+                    glfw::Key::Enter => {
+                        upgrading = false;
+                        actors.set_player_stats(profile.get_stats());
+                        restart(&mut actors, &mut game, &mut race, &mut world);
+                        achievements.restart();
+                        director.restart();
+                    },
+                    glfw::Key::D => {
+                        difficulty = difficulty.next();
+                        director = load_director();
+                        set_difficulty(&difficulty, &mut director, &mut actors, &mut game, &scores);
+                    },
+                    _ => match get_upgrade(key) {
+                        Some(u) => if profile.buy(&u) { save_profile(&profile); },
+                        None    => ()
+                    }
+                }
+            }
         }
 
         let t2 = time::get_time();
@@ -212,7 +289,8 @@ fn main() {
             t = t2;
 
 
-            calculate_collisions(&actors, &mut messages);
+            collisions::calculate_collisions(&actors, &mut messages);
+            achievements.process_player_messages(&messages);
 
 
             let mut output_messages = vec!();
@@ -220,16 +298,66 @@ fn main() {
 
             cam_pos = get_camera(&actors, cam_pos.clone());
 
-            draw_scene(&actors, loc, cam, color, z, cam_pos.clone(), &mut window, &background);
+            draw_scene(&actors, loc, cam, color, z, cam_pos.clone(), &mut window, &background, &game, &race, &achievements);
 
             actors.process_messages(&mut output_messages);
+            match race {
+                Some(ref mut r) => {
+                    let player = actors.get().into_iter().find(|a| a.id == 1);
+                    r.update(player.as_ref(), actors.get_player_input());
+                    r.process_messages(&output_messages);
+                },
+                None => ()
+            }
+            achievements.process_messages(&output_messages);
+            achievements.tick();
+            if achievements.new_unlocks() {
+                match achievements.save(ACHIEVEMENTS_PATH) {
+                    Ok(_)   => (),
+                    Err(e)  => println!("couldn't save achievements: {}", e)
+                }
+            }
+
             game.process_messages(output_messages);
+            game.tick();
+
+            let velocity = match actors.get_player() {
+                Some(p) => (p.vx, p.vy),
+                None    => (0.0, 0.0)
+            };
+            director.spawn(&mut actors, cam_pos.clone(), velocity, game.max_players(), game.tokens, &mut world);
+
+            if game.boss_due(!actors.get_bosses().is_empty()) {
+                spawn_boss(&mut actors, cam_pos.clone(), &mut world);
+            }
 
-            generate_actors(&mut actors, cam_pos.clone(), game.max_players());
             background.cleanup(cam_pos.clone());
             background.offscreen_generate(cam_pos.clone());
 
-            window.set_title(&format!("rusteroids - score [{}] - highscore [{}]", game.score, game.highscore)[..]);
+            let mut mode = match daily {
+                Some(ref d) => format!(" - {}", d.summary()),
+                None        => String::new()
+            };
+            if actors.get_agent_kind(1) == Some(agent::Kind::Autopilot) {
+                mode.push_str(" - autopilot");
+            } else if actors.get_agent_kind(1) == Some(agent::Kind::Replay) {
+                mode.push_str(" - replay");
+            }
+            if game.rival_tokens > 0 {
+                mode.push_str(&format!(" - lost to rivals [{}]", game.rival_tokens));
+            }
+
+            if let Some((name, _)) = achievements.get_toast() {
+                window.set_title(&format!("rusteroids - achievement unlocked - {}", name)[..]);
+            } else if let Some(ref r) = race {
+                window.set_title(&format!("rusteroids - {}", r.summary())[..]);
+            } else if upgrading {
+                window.set_title(&format!("rusteroids - upgrades - {} - d: difficulty [{}]", profile.summary(), difficulty.name())[..]);
+            } else if check_restart(&actors) {
+                window.set_title(&format!("rusteroids - {} - highscore [{}] {} - {}{}", game.summary(), game.highscore, difficulty.name(), achievements.summary(), mode)[..]);
+            } else {
+                window.set_title(&format!("rusteroids - score [{}] x{} - highscore [{}] {}{}", game.score, game.multiplier(), game.highscore, difficulty.name(), mode)[..]);
+            }
 
             // every second
             let t3 = time::get_time();
@@ -257,12 +385,47 @@ fn main() {
                     println!(":::::::::::::::::::::::::::::::::::::::\n");
                 }
 
-                if check_restart(&actors){
+                let race_over = match race {
+                    Some(ref r) => r.is_finished(),
+                    None        => false
+                };
+
+                if (check_restart(&actors) || race_over) && !upgrading {
 
                     if reset_countdown > 0 {
                         reset_countdown -= 1;
+                    } else if race.is_some() || daily.is_some() {
+                        match daily {
+                            Some(ref mut d) => {
+                                if d.record(game.score) {
+                                    match d.save_results(DAILY_PATH) {
+                                        Ok(_)   => (),
+                                        Err(e)  => println!("couldn't save daily results: {}", e)
+                                    }
+                                }
+                            },
+                            None => ()
+                        }
+
+                        restart(&mut actors, &mut game, &mut race, &mut world);
+                        achievements.restart();
+                        director.restart();
+                        match daily {
+                            Some(ref d) => start_daily(d, &mut actors, &mut world, &mut background),
+                            None        => ()
+                        }
+                        reset_countdown = 3;
                     } else {
-                        restart(&mut actors, &mut game);
+                        // bank the run and head to the upgrade screen
+                        profile.credits += game.credits();
+                        save_profile(&profile);
+                        if scores.record(&difficulty, game.score) {
+                            match scores.save(SCORES_PATH) {
+                                Ok(_)   => (),
+                                Err(e)  => println!("couldn't save high scores: {}", e)
+                            }
+                        }
+                        upgrading = true;
                         reset_countdown = 3;
                     }
                 }
@@ -295,30 +458,30 @@ fn main() {
     }
 }
 
-fn generate_actors(actors: &mut actor_manager::ActorManager, (cx, cy): (f32, f32), max_actors: usize){
-
-    let min_x = cx as i32 - 4000;
-    let max_x = cx as i32 + 4000;
-    let min_y = cy as i32 - 4000;
-    let max_y = cy as i32 + 4000;
-    let min_distance = 2600 * 2600; // square instead of sqrt on distance
-
-    while actors.get().len() < max_actors {
-        let x = rand::thread_rng().gen_range(min_x, max_x);
-        let y = rand::thread_rng().gen_range(min_y, max_y);
-
-        let x_dis = x - cx as i32;
-        let y_dis = y - cy as i32;
-        let distance = x_dis * x_dis + y_dis * y_dis;
-
-        if distance > min_distance {
-            let rand = rand::thread_rng().gen_range(0u32, 100);
-            match rand {
-                0...75  => actors.new_asteroid(x, y),
-                76...82 => actors.new_spaceship(x, y),
-                83...85 => actors.new_kamikaze(x, y, (cx, cy)),
-                _      => ()
-            }
+fn spawn_boss(actors: &mut actor_manager::ActorManager, (cx, cy): (f32, f32), world: &mut StdRng){
+    let angle = world.gen_range(0.0f32, 6.283);
+    let distance = 3000.0;
+    let x = cx + angle.sin() * distance;
+    let y = cy + angle.cos() * distance;
+    actors.new_mothership(x as i32, y as i32);
+}
+
+fn load_director() -> director::Director {
+    match director::Director::load(DIRECTOR_PATH) {
+        Ok(director) => director,
+        Err(e)       => {
+            println!("couldn't load {}: {}", DIRECTOR_PATH, e);
+            director::Director::new()
+        }
+    }
+}
+
+fn load_trees() -> spaceship_agent::Trees {
+    match spaceship_agent::Trees::load(PILOTS_PATH) {
+        Ok(trees) => trees,
+        Err(e)    => {
+            println!("couldn't load {}: {}", PILOTS_PATH, e);
+            spaceship_agent::Trees::new()
         }
     }
 }
@@ -334,61 +497,140 @@ fn check_restart(actors: &actor_manager::ActorManager) -> bool{
     !player_exists
 }
 
-fn restart(actors: &mut actor_manager::ActorManager, game: &mut game::Game){
-    game.restart();
-    actors.restart();
+// hand the player's ship over to the autopilot, or take it back
+fn toggle_autopilot(actors: &mut actor_manager::ActorManager, messages: &mut Vec<(i32, messages::PlayerInstructions)>){
+    if actors.get_agent_kind(1) == Some(agent::Kind::Autopilot) {
+        actors.assign(Box::new(agent::Keyboard::new(1)));
+        // let go of whatever the autopilot was holding down
+        messages.push((1, messages::PlayerInstructions::StopIncreaseThrottle));
+        messages.push((1, messages::PlayerInstructions::StopRotateLeft));
+        messages.push((1, messages::PlayerInstructions::StopRotateRight));
+    } else {
+        actors.assign(Box::new(agent::Autopilot::new(1)));
+    }
 }
 
+// watch the best race so far flown again, from the start
+fn watch_replay(actors: &mut actor_manager::ActorManager, game: &mut game::Game, race: &mut Option<race::Race>, world: &mut StdRng,
+                director: &mut director::Director){
+    let replay = match *race {
+        Some(ref r) => r.get_replay(),
+        None        => None
+    };
+
+    match replay {
+        Some(replay) => {
+            restart(actors, game, race, world);
+            director.restart();
+            actors.assign(Box::new(replay));
+        },
+        None => ()
+    }
+}
 
-fn calculate_collisions(actor_manager: &actor_manager::ActorManager, messages: &mut Vec<(i32, messages::PlayerInstructions)>){
+fn restart(actors: &mut actor_manager::ActorManager, game: &mut game::Game, race: &mut Option<race::Race>, world: &mut StdRng){
+    game.restart();
 
-    let actors = actor_manager.get();
+    // every attempt at a race gets the same world, so runs can be replayed
+    match *race {
+        Some(ref r) => {
+            actors.set_seed(Some(r.seed));
+            *world = StdRng::seed_from_u64(r.seed);
+        },
+        None => ()
+    }
+    actors.restart();
 
-    for a1 in actors.iter(){
+    match *race {
+        Some(ref mut r) => {
+            r.restart();
+            actors.set_course(r.get_checkpoints());
+        },
+        None => ()
+    }
+}
 
-        // todo - if we count the top iter(), we can slice this one
-        //      - to only match remaining actors
-        //      - would then need to push a2.id messages on collision
+// `--difficulty easy|normal|hard|insane`, normal if it's missing or unknown
+fn get_difficulty() -> difficulty::Difficulty {
+    let args: Vec<String> = env::args().collect();
+    match args.iter().position(|a| a == "--difficulty").and_then(|i| args.get(i + 1)) {
+        Some(name) => match difficulty::Difficulty::from_name(name) {
+            Some(d) => d,
+            None    => {
+                println!("unknown difficulty '{}' - playing on normal", name);
+                difficulty::Difficulty::Normal
+            }
+        },
+        None => difficulty::Difficulty::Normal
+    }
+}
 
-        let actors2 = actor_manager.get();
+// the director wants a freshly loaded table, or the changes stack up
+fn set_difficulty(d: &difficulty::Difficulty, director: &mut director::Director, actors: &mut actor_manager::ActorManager,
+                  game: &mut game::Game, scores: &difficulty::HighScores){
+    d.apply(director);
+    actors.set_skill(d.get_skill());
+    actors.set_ai_stats(d.get_stats());
+    game.highscore = scores.best(d);
+}
 
-        for a2 in actors2.iter(){
+fn save_profile(profile: &profile::Profile){
+    match profile.save(PROFILE_PATH) {
+        Ok(_)   => (),
+        Err(e)  => println!("couldn't save profile: {}", e)
+    }
+}
 
-            if     a1.id    == 0
-                || a2.id    == 0
-                || a1.id    == a2.id
-                || a1.id    == a2.parent
-                || a2.id    == a1.parent
-                || a1.collision_type == actor::CollisionType::Ignore
-                || a2.collision_type == actor::CollisionType::Ignore {
-                continue;
-            }
+fn get_upgrade(key: glfw::Key) -> Option<profile::Upgrade> {
+    match key {
+        glfw::Key::Num1 => Some(profile::Upgrade::Thrust),
+        glfw::Key::Num2 => Some(profile::Upgrade::Rotation),
+        glfw::Key::Num3 => Some(profile::Upgrade::FireRate),
+        glfw::Key::Num4 => Some(profile::Upgrade::Shield),
+        glfw::Key::Num5 => Some(profile::Upgrade::Hull),
+        glfw::Key::Num6 => Some(profile::Upgrade::Weapon),
+        _               => None
+    }
+}
 
-            if (a1.x - a2.x).abs() as usize > 1000 || (a1.y - a2.y).abs() as usize > 1000 {
-                continue;
-            }
+// `--daily` plays today's challenge
+fn get_daily() -> Option<daily::Daily> {
+    if !env::args().any(|a| a == "--daily") {
+        return None;
+    }
 
-            if a1.x + a1.width > a2.x - a2.width && a1.x - a1.width < a2.x + a2.width
-              && a1.y + a1.height > a2.y - a2.height && a1.y - a1.height < a2.y + a2.height {
+    let now = time::get_time();
+    let date = format!("{}", time::at_utc(now).strftime("%Y-%m-%d").unwrap());
+    let mut d = daily::Daily::new(date, (now.sec / 86400) as u64);
+    d.load_results(DAILY_PATH);
+    Some(d)
+}
 
-                // within bounding box - pixel perfect that shit
-                // ...
+// put the world back to how today's challenge starts
+fn start_daily(d: &daily::Daily, actors: &mut actor_manager::ActorManager, world: &mut StdRng, background: &mut background::Background){
+    actors.set_seed(Some(d.seed));
+    actors.set_drag(d.get_drag());
+    actors.set_player_stats(d.get_stats());
+    actors.restart();
 
-                match a2.collision_type{
-                    actor::CollisionType::Collide => messages.push((a1.id,
-                        messages::PlayerInstructions::Collide)),
-                    actor::CollisionType::Collect => messages.push((a1.id,
-                    messages::PlayerInstructions::Collect)),
-                    _              => ()
-                }
+    *world = StdRng::seed_from_u64(d.seed);
+    background.seed(d.seed);
+    background.generate((0.0, 0.0));
+}
 
-            }
-        }
+// `--race [seed]` starts a time attack over a fixed course
+fn get_race() -> Option<race::Race> {
+    let args: Vec<String> = env::args().collect();
+    match args.iter().position(|a| a == "--race") {
+        Some(i) => {
+            let seed = args.get(i + 1).and_then(|s| s.parse().ok()).unwrap_or(1);
+            Some(race::Race::new(seed, RACE_CHECKPOINTS))
+        },
+        None => None
     }
-
 }
 
-fn handle_window_event(window: &mut glfw::Window, (_/*time*/, event): (f64, glfw::WindowEvent), messages : &mut Vec<(i32, messages::PlayerInstructions)>) {
+fn handle_window_event(window: &mut glfw::Window, (_/*time*/, event): (f64, glfw::WindowEvent), messages : &mut Vec<(i32, messages::PlayerInstructions)>, menu: &mut Vec<glfw::Key>) {
     match event {
         // glfw::PosEvent(x, y)                => window.set_title(format!("Time: {}, Window pos: ({}, {})", time, x, y).as_slice()),
         // glfw::SizeEvent(w, h)               => window.set_title(format!("Time: {}, Window size: ({}, {})", time, w, h).as_slice()),
@@ -420,6 +662,19 @@ fn handle_window_event(window: &mut glfw::Window, (_/*time*/, event): (f64, glfw
                 (glfw::Key::Space, glfw::Action::Release) => messages.push((1, messages::PlayerInstructions::Fire)),
                 (glfw::Key::LeftShift, glfw::Action::Press) => messages.push((1, messages::PlayerInstructions::ShieldUp)),
                 (glfw::Key::LeftShift, glfw::Action::Release) => messages.push((1, messages::PlayerInstructions::ShieldDown)),
+                (glfw::Key::M, glfw::Action::Press) => messages.push((1, messages::PlayerInstructions::DropMine)),
+                (glfw::Key::H, glfw::Action::Press) => messages.push((1, messages::PlayerInstructions::Hyperspace)),
+                (glfw::Key::T, glfw::Action::Press) => messages.push((1, messages::PlayerInstructions::Tractor)),
+                (glfw::Key::Num1, glfw::Action::Press)
+                | (glfw::Key::Num2, glfw::Action::Press)
+                | (glfw::Key::Num3, glfw::Action::Press)
+                | (glfw::Key::Num4, glfw::Action::Press)
+                | (glfw::Key::Num5, glfw::Action::Press)
+                | (glfw::Key::Num6, glfw::Action::Press)
+                | (glfw::Key::D, glfw::Action::Press)
+                | (glfw::Key::P, glfw::Action::Press)
+                | (glfw::Key::R, glfw::Action::Press)
+                | (glfw::Key::Enter, glfw::Action::Press) => menu.push(key),
                 // (glfw::KeyR, glfw::Press) => {
                 //     // Resize should cause the window to "refresh"
                 //     let (window_width, window_height) = window.get_size();
@@ -452,7 +707,10 @@ fn draw_scene(actor_manager:&actor_manager::ActorManager,
         z: i32,
         (cx, cy):(f32, f32),
         window: &mut glfw::Window,
-        background: &background::Background){
+        background: &background::Background,
+        game: &game::Game,
+        race: &Option<race::Race>,
+        achievements: &achievements::Achievements){
 
     let actors = actor_manager.get();
     let bg = background.get();
@@ -466,17 +724,44 @@ fn draw_scene(actor_manager:&actor_manager::ActorManager,
     }
 
     let mut meter = 0.0;
+    let mut cooldown = 0.0;
+    let mut hull = 0.0;
     for v in actors.iter() {
         if v.id == 1 {
             meter = v.meter;
+            cooldown = v.cooldown;
+            hull = v.hull;
+
+            // the best run so far, flying alongside
+            match *race {
+                Some(ref r) => match r.get_ghost() {
+                    Some((gx, gy, gr)) => draw(&v.shape, loc, cam, color, z, gx, gy, gr, cx, cy, &vec!(0.3, 0.3, 0.45), 1.0),
+                    None               => ()
+                },
+                None => ()
+            }
         }
 
         draw_actor(v, loc, cam, color, z, cx, cy);
     }
 
+    for (ship, target) in actor_manager.get_tethers().into_iter(){
+        draw_beam(loc, cam, color, z, (cx, cy), (ship.x, ship.y), (target.x, target.y));
+    }
+
     let collectables = actor_manager.get_collectables();
+    let bosses = actor_manager.get_bosses();
+    let rivals = actor_manager.get_rivals();
+    let next_checkpoint = actor_manager.get_next_checkpoint();
+
+    let toast = match achievements.get_toast() {
+        Some((_, t)) => t,
+        None         => 0.0
+    };
 
-    draw_hud(loc, cam, color, z, (cx, cy), collectables, meter);
+    let game_over = check_restart(actor_manager);
+
+    draw_hud(loc, cam, color, z, (cx, cy), collectables, rivals, next_checkpoint, meter, cooldown, hull, bosses, game, toast, game_over);
 
     window.swap_buffers();
 }
@@ -493,41 +778,44 @@ fn draw_actor(p: &actor::ActorView, loc:i32, cam:i32, color:i32, z:i32, cx: f32,
     }
 }
 
-fn draw_hud(loc:i32, cam:i32, color:i32, z:i32, (cx, cy) : (f32, f32), collectables : Vec<actor::ActorView>, meter: f32){
+fn draw_beam(loc:i32, cam:i32, color:i32, z:i32, (cx, cy) : (f32, f32), (sx, sy) : (f32, f32), (tx, ty) : (f32, f32)){
+    let dx = tx - sx;
+    let dy = ty - sy;
+    let length = (dx * dx + dy * dy).sqrt() / 2000.0;
+    let w = 0.003;
+
     let v = vec!(
-        0.0, 0.0,
-        0.04, -0.04,
-        0.0, -0.02,
+        -w, 0.0,
+        w, 0.0,
+        w, length,
 
-        0.0, -0.02,
-        -0.04, -0.04,
-        0.0, 0.0
+        w, length,
+        -w, length,
+        -w, 0.0
     );
 
+    let r = rand::thread_rng().gen_range(0.5f32, 0.8);
+    draw(&v, loc, cam, color, z, sx, sy, dx.atan2(dy), cx, cy, &vec!(0.4, r, 0.9), 1.0);
+}
+
+fn draw_hud(loc:i32, cam:i32, color:i32, z:i32, (cx, cy) : (f32, f32), collectables : Vec<actor::ActorView>, rivals: Vec<actor::ActorView>, next_checkpoint: Option<(i32, i32)>, meter: f32, cooldown: f32, hull: f32, bosses: Vec<actor::ActorView>, game: &game::Game, toast: f32, game_over: bool){
     let col = vec!(
         0.9, 0.9, 0.4
     );
 
     for token in collectables.iter(){
-        let dx = token.x - cx;
-        let dy = token.y - cy;
-        let rotation = dx.atan2(dy);
-
-        let player_distance = (dx * dx + dy * dy).sqrt() as i32;
-
-        let dx = rotation.sin();
-        let dy = rotation.cos();
-
-        let mut distance = 1800;
-
-        while distance > player_distance - 100 {
-            distance -= 5;
-        }
+        draw_arrow(loc, cam, color, z, (cx, cy), (token.x, token.y), &col);
+    }
 
-        let x = dx * (distance as f32);
-        let y = dy * (distance as f32);
+    // a faint arrow to the checkpoint after this one
+    match next_checkpoint {
+        Some((x, y)) => draw_arrow(loc, cam, color, z, (cx, cy), (x as f32, y as f32), &vec!(0.4, 0.4, 0.25)),
+        None         => ()
+    }
 
-        draw(&v, loc, cam, color, z, x, y, rotation, 0.0, 0.0, &col, 1.0);
+    // rivals are after the same token - point them out in their own colour
+    for rival in rivals.iter(){
+        draw_arrow(loc, cam, color, z, (cx, cy), (rival.x, rival.y), &faction::Faction::Rivals.color());
     }
 
     let m = meter/5.0;
@@ -546,6 +834,175 @@ fn draw_hud(loc:i32, cam:i32, color:i32, z:i32, (cx, cy) : (f32, f32), collectab
 
     draw(&v, loc, cam, color, z, 1500.0, -1850.0, 0.0, 0.0, 0.0, &col, 1.0);
 
+    // hyperspace charge - fills back up as the cooldown runs out
+    let m = (1.0 - cooldown) / 5.0;
+    let v = vec!(
+        0.0, 0.0,
+        0.0, -h / 2.0,
+        m, 0.0,
+
+        m, 0.0,
+        m, -h / 2.0,
+        0.0, -h / 2.0
+    );
+
+    let col = if cooldown > 0.0 { vec!(0.3, 0.3, 0.6) } else { vec!(0.5, 0.6, 1.0) };
+
+    draw(&v, loc, cam, color, z, 1500.0, -1910.0, 0.0, 0.0, 0.0, &col, 1.0);
+
+    // hull - goes from green to red as the ship takes damage
+    let m = hull / 5.0;
+    let v = vec!(
+        0.0, 0.0,
+        0.0, -h,
+        m, 0.0,
+
+        m, 0.0,
+        m, -h,
+        0.0, -h
+    );
+
+    let col = vec!(0.9 - hull * 0.6, 0.2 + hull * 0.5, 0.2);
+
+    draw(&v, loc, cam, color, z, 1500.0, -1950.0, 0.0, 0.0, 0.0, &col, 1.0);
+
+    // combo timer and multiplier pips
+    if game.combo_meter() > 0.0 {
+        let m = game.combo_meter() / 5.0;
+        let v = vec!(
+            0.0, 0.0,
+            0.0, -h,
+            m, 0.0,
+
+            m, 0.0,
+            m, -h,
+            0.0, -h
+        );
+
+        draw(&v, loc, cam, color, z, 1500.0, -1790.0, 0.0, 0.0, 0.0, &vec!(0.9, 0.6, 0.2), 1.0);
+    }
+
+    let pip = vec!(
+        0.0, 0.0,
+        0.0, -0.02,
+        0.02, 0.0,
+
+        0.02, 0.0,
+        0.02, -0.02,
+        0.0, -0.02
+    );
+
+    for i in 0..game.multiplier(){
+        draw(&pip, loc, cam, color, z, 1500.0 + i as f32 * 60.0, -1720.0, 0.0, 0.0, 0.0, &vec!(0.9, 0.6, 0.2), 1.0);
+    }
+
+    // mothership health bar
+    for boss in bosses.iter().take(1){
+        let h = 0.03;
+        let full = 1.0;
+        let m = boss.meter * full;
+
+        let back = vec!(
+            0.0, 0.0,
+            0.0, -h,
+            full, 0.0,
+
+            full, 0.0,
+            full, -h,
+            0.0, -h
+        );
+
+        let v = vec!(
+            0.0, 0.0,
+            0.0, -h,
+            m, 0.0,
+
+            m, 0.0,
+            m, -h,
+            0.0, -h
+        );
+
+        draw(&back, loc, cam, color, z, -1000.0, 1900.0, 0.0, 0.0, 0.0, &vec!(0.3, 0.1, 0.1), 1.0);
+        draw(&v, loc, cam, color, z, -1000.0, 1900.0, 0.0, 0.0, 0.0, &vec!(0.8, 0.2, 0.2), 1.0);
+    }
+
+    // game over - where the score came from, tokens in yellow then kills in
+    // orange, with the best multiplier and the tokens lost to rivals as pips
+    if game_over {
+        let h = 0.04;
+        let full = 1.2;
+        let (tokens, kills) = game.breakdown();
+        let bar = |w: f32| vec!(
+            0.0, 0.0,
+            0.0, -h,
+            w, 0.0,
+
+            w, 0.0,
+            w, -h,
+            0.0, -h
+        );
+
+        draw(&bar(full), loc, cam, color, z, -1200.0, 300.0, 0.0, 0.0, 0.0, &vec!(0.2, 0.2, 0.3), 1.0);
+        draw(&bar(full * tokens), loc, cam, color, z, -1200.0, 300.0, 0.0, 0.0, 0.0, &vec!(0.9, 0.9, 0.4), 1.0);
+        draw(&bar(full * kills), loc, cam, color, z, -1200.0 + full * tokens * 2000.0, 300.0, 0.0, 0.0, 0.0, &vec!(0.9, 0.6, 0.2), 1.0);
+
+        for i in 0..game.best_multiplier(){
+            draw(&pip, loc, cam, color, z, -1200.0 + i as f32 * 60.0, 160.0, 0.0, 0.0, 0.0, &vec!(0.9, 0.6, 0.2), 1.0);
+        }
+        for i in 0..game.rival_tokens{
+            draw(&pip, loc, cam, color, z, -1200.0 + i as f32 * 60.0, 80.0, 0.0, 0.0, 0.0, &faction::Faction::Rivals.color(), 1.0);
+        }
+    }
+
+    // achievement toast - a gold banner that shrinks away
+    if toast > 0.0 {
+        let h = 0.04;
+        let w = toast * 0.6;
+        let v = vec!(
+            -w, 0.0,
+            -w, -h,
+            w, 0.0,
+
+            w, 0.0,
+            w, -h,
+            -w, -h
+        );
+
+        draw(&v, loc, cam, color, z, 0.0, 1800.0, 0.0, 0.0, 0.0, &vec!(0.95, 0.8, 0.3), 1.0);
+    }
+
+}
+
+fn draw_arrow(loc:i32, cam:i32, color:i32, z:i32, (cx, cy) : (f32, f32), (tx, ty) : (f32, f32), col: &Vec<f32>){
+    let v = vec!(
+        0.0, 0.0,
+        0.04, -0.04,
+        0.0, -0.02,
+
+        0.0, -0.02,
+        -0.04, -0.04,
+        0.0, 0.0
+    );
+
+    let dx = tx - cx;
+    let dy = ty - cy;
+    let rotation = dx.atan2(dy);
+
+    let player_distance = (dx * dx + dy * dy).sqrt() as i32;
+
+    let dx = rotation.sin();
+    let dy = rotation.cos();
+
+    let mut distance = 1800;
+
+    while distance > player_distance - 100 {
+        distance -= 5;
+    }
+
+    let x = dx * (distance as f32);
+    let y = dy * (distance as f32);
+
+    draw(&v, loc, cam, color, z, x, y, rotation, 0.0, 0.0, col, 1.0);
 }
 
 fn draw(v: &Vec<f32>, loc:i32, cam:i32, color:i32, z:i32, x:f32, y:f32, rotation:f32, cx:f32, cy:f32, col:&Vec<f32>, z_val:f32){
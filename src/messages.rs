@@ -1,3 +1,9 @@
+use asteroid::Material;
+use faction::Faction;
+use loot::Loot;
+use loot::LootTable;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum PlayerInstructions {
     BeginIncreaseThrottle,
     BeginDecreaseThrottle,
@@ -8,16 +14,59 @@ pub enum PlayerInstructions {
     StopRotateRight,
     StopRotateLeft,
     Fire,
-    Collide,
-    Collect,
+    Impact(f32, f32, i32),
+    // how much damage the hit does
+    Collide(f32),
+    Deflect(f32, f32, i32, Faction, f32),
+    // the token's id
+    Collect(i32),
     ShieldUp,
     ShieldDown,
+    DropMine,
+    Hyperspace,
+    Trigger,
+    PartDestroyed,
+    EngineDestroyed,
+    PickedUp(i32),
+    Loot(Loot),
+    Tractor,
+    Tether(i32),
+    Release,
+    // nowhere safe to jump to - the drive doesn't fire
+    HyperspaceBlocked,
+}
+
+impl PlayerInstructions {
+    // the ones a pilot sends, as opposed to what the game tells a ship
+    pub fn is_control(&self) -> bool {
+        match *self {
+            PlayerInstructions::BeginIncreaseThrottle | PlayerInstructions::BeginDecreaseThrottle |
+            PlayerInstructions::StopIncreaseThrottle  | PlayerInstructions::StopDecreaseThrottle  |
+            PlayerInstructions::BeginRotateRight      | PlayerInstructions::BeginRotateLeft       |
+            PlayerInstructions::StopRotateRight       | PlayerInstructions::StopRotateLeft        |
+            PlayerInstructions::Fire      | PlayerInstructions::ShieldUp  | PlayerInstructions::ShieldDown |
+            PlayerInstructions::DropMine  | PlayerInstructions::Hyperspace | PlayerInstructions::Tractor => true,
+            _ => false
+        }
+    }
 }
 
 pub enum GameInstructions {
     Fire,
     Explode,
+    Hit,
     Trail,
-    NewAsteroid,
-    Collect
+    NewAsteroid(Material),
+    Collect(i32),
+    DropMine,
+    Hyperspace,
+    Detonate,
+    Blast,
+    Kill(i32),
+    Launch,
+    PartDestroyed,
+    EngineDestroyed,
+    DropLoot(LootTable),
+    Loot(Loot, i32),
+    Tractor
 }
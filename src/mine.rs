@@ -0,0 +1,136 @@
+use actor::Actor;
+use actor::ActorView;
+use rand::Rng;
+use actor;
+use messages::PlayerInstructions;
+use messages::GameInstructions;
+use faction::Faction;
+
+static PI : f32 = 3.14159265359;
+static ARM_TIME: usize = 90;
+pub static TRIGGER_RADIUS: f32 = 350.0;
+pub static BLAST_RADIUS: f32 = 600.0;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mine{
+    id: i32,
+    parent: i32,
+    faction: Faction,
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    rotation: f32,
+    shape: Vec<f32>,
+    is_alive:bool,
+    color: Vec<f32>,
+    armed_color: Vec<f32>,
+    arm_timer: usize
+}
+
+impl Mine{
+    pub fn new<R: Rng>(id: i32, parent: i32, x: i32, y: i32, faction: Faction, rng: &mut R) -> Mine {
+
+        let vx = rng.gen_range(-1.0f32, 1.0);
+        let vy = rng.gen_range(-1.0f32, 1.0);
+
+        let shape = vec!(
+            0.0,   0.02,
+            0.02,  0.0,
+            0.0,  -0.02,
+
+            0.0,  -0.02,
+            -0.02, 0.0,
+            0.0,   0.02
+        );
+
+        Mine{
+            id: id, parent: parent, faction: faction, x: x as f32, y: y as f32,
+            vx: vx, vy: vy,
+            rotation: 0.0,
+            shape: shape,
+            is_alive: true,
+            color: vec!(0.5, 0.5, 0.5),
+            armed_color: vec!(0.9, 0.2, 0.1),
+            arm_timer: 0
+        }
+    }
+
+    fn is_armed(&self) -> bool {
+        self.arm_timer >= ARM_TIME
+    }
+
+    fn detonate(&mut self, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        self.is_alive = false;
+        output_messages.push((GameInstructions::Detonate, self.get_view().clone()));
+    }
+}
+
+
+impl Actor for Mine{
+
+    fn update(&mut self, _:&mut Vec<(GameInstructions, ActorView)>){
+        self.x += self.vx;
+        self.y += self.vy;
+        self.rotation += 2.0;
+
+        if !self.is_armed() {
+            self.arm_timer += 1;
+        }
+    }
+
+    fn get_view(&self) -> ActorView {
+        // blink once armed
+        let show_armed = self.is_armed() && (self.rotation as i32 / 30) % 2 == 0;
+
+        ActorView {
+            id: self.id,
+            parent: self.parent,
+            faction: self.faction.clone(),
+            x: self.x,
+            y: self.y,
+            vx: self.vx,
+            vy: self.vy,
+            width: 40.0,
+            height: 40.0,
+            rotation: (self.rotation * PI) / 180.0,
+            shape: self.shape.clone(),
+            color: if show_armed { self.armed_color.clone() } else { self.color.clone() },
+            collision_type: actor::CollisionType::Collide,
+            show_secondary: false,
+            secondary_shape: None,
+            secondary_color: None,
+            meter: self.arm_timer as f32 / ARM_TIME as f32,
+            cooldown: 0.0,
+            damage: 0.0,
+            hull: 1.0
+        }
+    }
+
+    fn execute(&mut self, message: &PlayerInstructions, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        match message {
+            &PlayerInstructions::Collide(_)
+            | &PlayerInstructions::Deflect(..) => self.detonate(output_messages),
+            &PlayerInstructions::Trigger    => {
+                                            if self.is_armed() {
+                                                self.detonate(output_messages);
+                                            }
+                                        },
+            _                           => ()
+        };
+    }
+
+    fn kill(&mut self){
+        self.is_alive = false;
+    }
+
+    fn get_id(&self) -> i32{
+        self.id
+    }
+
+    fn is_alive(&self) -> bool{
+        self.is_alive
+    }
+
+}
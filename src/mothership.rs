@@ -0,0 +1,372 @@
+use actor::Actor;
+use actor::ActorView;
+use actor;
+use messages::PlayerInstructions;
+use messages::GameInstructions;
+use faction::Faction;
+
+static PI : f32 = 3.14159265359;
+static MAX_HEALTH: i32 = 40;
+static PART_HEALTH: i32 = 6;
+static LAUNCH_TIME: i32 = 240;
+static LAUNCH_RANGE: f32 = 3000.0;
+static TURRET_RANGE: f32 = 1800.0;
+static SPEED: f32 = 3.0;
+static TURN_RATE: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartKind {
+    Turret,
+    Engine
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mothership{
+    id: i32,
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    rotation: f32,
+    shape: Vec<f32>,
+    is_alive:bool,
+    color: Vec<f32>,
+    hit_color: Vec<f32>,
+    health: i32,
+    hit_timer: i32,
+    engines: i32,
+    max_engines: i32,
+    launch_countdown: i32,
+    target: (f32, f32)
+}
+
+impl Mothership{
+    pub fn new(id: i32, x: i32, y: i32, rotation: f32, engines: i32) -> Mothership {
+        let shape = vec!(
+            0.0,   0.2,
+            0.15,  0.05,
+            -0.15, 0.05,
+
+            0.15,  0.05,
+            0.15, -0.2,
+            -0.15, 0.05,
+
+            -0.15, 0.05,
+            0.15, -0.2,
+            -0.15, -0.2
+        );
+
+        Mothership{
+            id: id, x: x as f32, y: y as f32,
+            vx: 0.0, vy: 0.0,
+            rotation: rotation,
+            shape: shape,
+            is_alive: true,
+            color: vec!(0.35, 0.2, 0.4),
+            hit_color: vec!(0.9, 0.9, 0.9),
+            health: MAX_HEALTH,
+            hit_timer: 0,
+            engines: engines,
+            max_engines: engines,
+            launch_countdown: LAUNCH_TIME,
+            target: (x as f32, y as f32)
+        }
+    }
+
+    pub fn track(&mut self, target: (f32, f32)){
+        self.target = target;
+    }
+
+    fn target_distance(&self) -> f32 {
+        let (tx, ty) = self.target;
+        let dx = tx - self.x;
+        let dy = ty - self.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    fn turn_to_target(&mut self){
+        let (tx, ty) = self.target;
+        let ideal_rotation = (tx - self.x).atan2(ty - self.y) * 180.0 / PI;
+        let mut d_rotation = ideal_rotation - self.rotation;
+
+        while d_rotation > 180.0 {
+            d_rotation -= 360.0;
+        }
+
+        while d_rotation < -180.0 {
+            d_rotation += 360.0;
+        }
+
+        if d_rotation > TURN_RATE {
+            self.rotation += TURN_RATE;
+        } else if d_rotation < -TURN_RATE {
+            self.rotation -= TURN_RATE;
+        }
+    }
+
+    fn get_rotate_vec(&self) -> (f32, f32){
+        let r = (self.rotation * PI) / 180.0;
+        (r.sin(), r.cos())
+    }
+
+    fn take_damage(&mut self, damage: i32, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        self.health -= damage;
+        self.hit_timer = 4;
+
+        if self.health <= 0 {
+            self.is_alive = false;
+            output_messages.push((GameInstructions::Explode, self.get_view().clone()));
+        }
+    }
+}
+
+
+impl Actor for Mothership{
+
+    fn update(&mut self, output_messages: &mut Vec<(GameInstructions, ActorView)>){
+        self.turn_to_target();
+
+        // every lost engine slows the ship down
+        let speed = SPEED * self.engines as f32 / self.max_engines as f32;
+        let (dirx, diry) = self.get_rotate_vec();
+        self.vx = dirx * speed;
+        self.vy = diry * speed;
+        self.x += self.vx;
+        self.y += self.vy;
+
+        if self.hit_timer > 0 {
+            self.hit_timer -= 1;
+        }
+
+        if self.launch_countdown > 0 {
+            self.launch_countdown -= 1;
+        } else if self.target_distance() < LAUNCH_RANGE {
+            output_messages.push((GameInstructions::Launch, self.get_view().clone()));
+            self.launch_countdown = LAUNCH_TIME;
+        }
+    }
+
+    fn get_view(&self) -> ActorView {
+        ActorView {
+            id: self.id,
+            parent: 0,
+            faction: Faction::Pirates,
+            x: self.x,
+            y: self.y,
+            vx: self.vx,
+            vy: self.vy,
+            width: 300.0,
+            height: 400.0,
+            rotation: (self.rotation * PI) / 180.0,
+            shape: self.shape.clone(),
+            color: if self.hit_timer > 0 { self.hit_color.clone() } else { self.color.clone() },
+            collision_type: actor::CollisionType::Collide,
+            show_secondary: false,
+            secondary_shape: None,
+            secondary_color: None,
+            meter: self.health as f32 / MAX_HEALTH as f32,
+            cooldown: 0.0,
+            damage: 100.0,
+            hull: 1.0
+        }
+    }
+
+    fn execute(&mut self, message: &PlayerInstructions, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        match message {
+            &PlayerInstructions::Collide(_)
+            | &PlayerInstructions::Deflect(..)    => self.take_damage(1, output_messages),
+            &PlayerInstructions::PartDestroyed    => self.take_damage(PART_HEALTH, output_messages),
+            &PlayerInstructions::EngineDestroyed  => {
+                                            self.engines -= 1;
+                                            self.take_damage(PART_HEALTH, output_messages);
+                                        },
+            _                           => ()
+        };
+    }
+
+    fn kill(&mut self){
+        self.is_alive = false;
+    }
+
+    fn get_id(&self) -> i32{
+        self.id
+    }
+
+    fn is_alive(&self) -> bool{
+        self.is_alive
+    }
+
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MothershipPart{
+    id: i32,
+    parent: i32,
+    kind: PartKind,
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    offset_x: f32,
+    offset_y: f32,
+    rotation: f32,
+    shape: Vec<f32>,
+    is_alive:bool,
+    color: Vec<f32>,
+    hit_color: Vec<f32>,
+    health: i32,
+    hit_timer: i32,
+    fire_countdown: i32,
+    target: (f32, f32)
+}
+
+impl MothershipPart{
+    pub fn new(id: i32, parent: i32, kind: PartKind, offset_x: f32, offset_y: f32) -> MothershipPart {
+        let (shape, color) = match kind {
+            PartKind::Turret => (vec!(
+                0.0,   0.05,
+                0.03, -0.03,
+                -0.03, -0.03
+            ), vec!(0.6, 0.3, 0.5)),
+            PartKind::Engine => (vec!(
+                -0.03,  0.03,
+                0.03,   0.03,
+                0.03,  -0.03,
+
+                0.03,  -0.03,
+                -0.03, -0.03,
+                -0.03,  0.03
+            ), vec!(0.8, 0.5, 0.2))
+        };
+
+        MothershipPart{
+            id: id, parent: parent, kind: kind,
+            x: 0.0, y: 0.0,
+            vx: 0.0, vy: 0.0,
+            offset_x: offset_x, offset_y: offset_y,
+            rotation: 0.0,
+            shape: shape,
+            is_alive: true,
+            color: color,
+            hit_color: vec!(0.9, 0.9, 0.9),
+            health: PART_HEALTH,
+            hit_timer: 0,
+            fire_countdown: 60,
+            target: (0.0, 0.0)
+        }
+    }
+
+    pub fn get_parent(&self) -> i32 {
+        self.parent
+    }
+
+    // parts can't move on their own - the manager pins them to their mothership every tick
+    pub fn attach(&mut self, ship: &ActorView, target: (f32, f32)){
+        let r = ship.rotation;
+        self.x = ship.x + self.offset_x * r.cos() + self.offset_y * r.sin();
+        self.y = ship.y - self.offset_x * r.sin() + self.offset_y * r.cos();
+        self.vx = ship.vx;
+        self.vy = ship.vy;
+        self.target = target;
+
+        self.rotation = match self.kind {
+            PartKind::Turret => {
+                let (tx, ty) = target;
+                (tx - self.x).atan2(ty - self.y) * 180.0 / PI
+            },
+            PartKind::Engine => r * 180.0 / PI
+        };
+    }
+
+    fn target_distance(&self) -> f32 {
+        let (tx, ty) = self.target;
+        let dx = tx - self.x;
+        let dy = ty - self.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+}
+
+
+impl Actor for MothershipPart{
+
+    fn update(&mut self, output_messages: &mut Vec<(GameInstructions, ActorView)>){
+        if self.hit_timer > 0 {
+            self.hit_timer -= 1;
+        }
+
+        if self.kind != PartKind::Turret {
+            return;
+        }
+
+        if self.fire_countdown > 0 {
+            self.fire_countdown -= 1;
+        } else if self.target_distance() < TURRET_RANGE {
+            // bullets belong to the mothership so they pass through the hull
+            let mut view = self.get_view();
+            view.id = self.parent;
+            output_messages.push((GameInstructions::Fire, view));
+            self.fire_countdown = 45;
+        }
+    }
+
+    fn get_view(&self) -> ActorView {
+        ActorView {
+            id: self.id,
+            parent: self.parent,
+            faction: Faction::Pirates,
+            x: self.x,
+            y: self.y,
+            vx: self.vx,
+            vy: self.vy,
+            width: 60.0,
+            height: 60.0,
+            rotation: (self.rotation * PI) / 180.0,
+            shape: self.shape.clone(),
+            color: if self.hit_timer > 0 { self.hit_color.clone() } else { self.color.clone() },
+            collision_type: actor::CollisionType::Collide,
+            show_secondary: false,
+            secondary_shape: None,
+            secondary_color: None,
+            meter: self.health as f32 / PART_HEALTH as f32,
+            cooldown: 0.0,
+            damage: 40.0,
+            hull: 1.0
+        }
+    }
+
+    fn execute(&mut self, message: &PlayerInstructions, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        match message {
+            &PlayerInstructions::Collide(_)
+            | &PlayerInstructions::Deflect(..) => {
+                                            self.health -= 1;
+                                            self.hit_timer = 4;
+
+                                            if self.health <= 0 {
+                                                self.is_alive = false;
+                                                output_messages.push((GameInstructions::Explode, self.get_view().clone()));
+
+                                                let lost = match self.kind {
+                                                    PartKind::Turret => GameInstructions::PartDestroyed,
+                                                    PartKind::Engine => GameInstructions::EngineDestroyed
+                                                };
+                                                output_messages.push((lost, self.get_view().clone()));
+                                            }
+                                        },
+            _                           => ()
+        };
+    }
+
+    fn kill(&mut self){
+        self.is_alive = false;
+    }
+
+    fn get_id(&self) -> i32{
+        self.id
+    }
+
+    fn is_alive(&self) -> bool{
+        self.is_alive
+    }
+
+}
@@ -0,0 +1,128 @@
+use actor::Actor;
+use actor::ActorView;
+use actor;
+use loot::Loot;
+use messages::PlayerInstructions;
+use messages::GameInstructions;
+use faction::Faction;
+
+static PI : f32 = 3.14159265359;
+static LIFETIME: usize = 900;
+static BLINK_TIME: usize = 180;
+
+// something dropped by a destroyed ship - drifts off and
+// disappears if nobody picks it up in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pickup{
+    id: i32,
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    rotation: f32,
+    loot: Loot,
+    shape: Vec<f32>,
+    color: Vec<f32>,
+    lifetime: usize,
+    is_alive: bool
+}
+
+impl Pickup{
+    pub fn new(id: i32, x: i32, y: i32, (vx, vy): (f32, f32), loot: Loot) -> Pickup {
+        let shape = vec!(
+            0.0,  0.03,
+            0.03, 0.0,
+            0.0, -0.03,
+
+            0.0, -0.03,
+            -0.03, 0.0,
+            0.0,  0.03
+        );
+
+        Pickup{
+            id: id, x: x as f32, y: y as f32,
+            vx: vx, vy: vy,
+            rotation: 0.0,
+            color: loot.color(),
+            loot: loot,
+            shape: shape,
+            lifetime: LIFETIME,
+            is_alive: true
+        }
+    }
+}
+
+
+impl Actor for Pickup{
+
+    fn update(&mut self, _:&mut Vec<(GameInstructions, ActorView)>){
+        self.x += self.vx;
+        self.y += self.vy;
+        self.vx *= 0.98;
+        self.vy *= 0.98;
+        self.rotation += 3.0;
+
+        self.lifetime -= 1;
+        if self.lifetime == 0 {
+            self.is_alive = false;
+        }
+    }
+
+    fn get_view(&self) -> ActorView {
+        // blink when it's about to go
+        let visible = self.lifetime > BLINK_TIME || (self.lifetime / 10) % 2 == 0;
+
+        ActorView {
+            id: self.id,
+            parent: 0,
+            faction: Faction::Unaligned,
+            x: self.x,
+            y: self.y,
+            vx: self.vx,
+            vy: self.vy,
+            width: 60.0,
+            height: 60.0,
+            rotation: (self.rotation * PI) / 180.0,
+            shape: self.shape.clone(),
+            color: if visible { self.color.clone() } else { vec!(0.2, 0.2, 0.3) },
+            collision_type: actor::CollisionType::Pickup,
+            show_secondary: false,
+            secondary_shape: None,
+            secondary_color: None,
+            meter: self.lifetime as f32 / LIFETIME as f32,
+            cooldown: 0.0,
+            damage: 0.0,
+            hull: 1.0
+        }
+    }
+
+    fn execute(&mut self, message: &PlayerInstructions, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        match message {
+            &PlayerInstructions::PickedUp(collector) => {
+                                            if self.is_alive {
+                                                self.is_alive = false;
+                                                output_messages.push((GameInstructions::Loot(self.loot.clone(), collector), self.get_view()));
+                                            }
+                                        },
+            _                           => ()
+        };
+    }
+
+    fn kill(&mut self){
+        self.is_alive = false;
+    }
+
+    fn apply_impulse(&mut self, dvx: f32, dvy: f32){
+        self.vx += dvx;
+        self.vy += dvy;
+    }
+
+    fn get_id(&self) -> i32{
+        self.id
+    }
+
+    fn is_alive(&self) -> bool{
+        self.is_alive
+    }
+
+}
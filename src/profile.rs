@@ -0,0 +1,230 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use spaceship::ShipStats;
+
+static MAX_LEVEL: usize = 5;
+static MAX_WEAPON_LEVEL: usize = 2;
+static UPGRADE_COST: usize = 20;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Upgrade {
+    Thrust,
+    Rotation,
+    FireRate,
+    Shield,
+    Hull,
+    Weapon
+}
+
+impl Upgrade {
+    pub fn all() -> Vec<Upgrade> {
+        vec!(Upgrade::Thrust, Upgrade::Rotation, Upgrade::FireRate,
+             Upgrade::Shield, Upgrade::Hull, Upgrade::Weapon)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Upgrade::Thrust     => "thrust",
+            Upgrade::Rotation   => "rotation",
+            Upgrade::FireRate   => "fire rate",
+            Upgrade::Shield     => "shield",
+            Upgrade::Hull       => "hull",
+            Upgrade::Weapon     => "weapon"
+        }
+    }
+
+    fn max_level(&self) -> usize {
+        match *self {
+            Upgrade::Weapon     => MAX_WEAPON_LEVEL,
+            _                   => MAX_LEVEL
+        }
+    }
+}
+
+// credits and upgrades that carry over from one run to the next
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile{
+    pub credits: usize,
+    thrust: usize,
+    rotation: usize,
+    fire_rate: usize,
+    shield: usize,
+    hull: usize,
+    weapon: usize
+}
+
+impl Profile{
+    pub fn new() -> Profile {
+        Profile {
+            credits: 0,
+            thrust: 0,
+            rotation: 0,
+            fire_rate: 0,
+            shield: 0,
+            hull: 0,
+            weapon: 0
+        }
+    }
+
+    // a missing or unreadable profile just means starting from scratch
+    pub fn load(path: &str) -> Profile {
+        let mut profile = Profile::new();
+        let mut contents = String::new();
+
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            Ok(_)   => (),
+            Err(_)  => return profile
+        }
+
+        for line in contents.lines() {
+            let mut parts = line.splitn(2, '=');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(k), Some(v)) => (k.trim(), v.trim().parse::<usize>().unwrap_or(0)),
+                _                  => continue
+            };
+
+            match key {
+                "credits"   => profile.credits = value,
+                "thrust"    => profile.thrust = value,
+                "rotation"  => profile.rotation = value,
+                "fire_rate" => profile.fire_rate = value,
+                "shield"    => profile.shield = value,
+                "hull"      => profile.hull = value,
+                "weapon"    => profile.weapon = value,
+                _           => ()
+            }
+        }
+
+        profile
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut f = File::create(path)?;
+        write!(f, "credits={}\n", self.credits)?;
+        write!(f, "thrust={}\n", self.thrust)?;
+        write!(f, "rotation={}\n", self.rotation)?;
+        write!(f, "fire_rate={}\n", self.fire_rate)?;
+        write!(f, "shield={}\n", self.shield)?;
+        write!(f, "hull={}\n", self.hull)?;
+        write!(f, "weapon={}\n", self.weapon)?;
+        Ok(())
+    }
+
+    pub fn level(&self, upgrade: &Upgrade) -> usize {
+        match *upgrade {
+            Upgrade::Thrust     => self.thrust,
+            Upgrade::Rotation   => self.rotation,
+            Upgrade::FireRate   => self.fire_rate,
+            Upgrade::Shield     => self.shield,
+            Upgrade::Hull       => self.hull,
+            Upgrade::Weapon     => self.weapon
+        }
+    }
+
+    // None once the upgrade is maxed out
+    pub fn cost(&self, upgrade: &Upgrade) -> Option<usize> {
+        let level = self.level(upgrade);
+        if level >= upgrade.max_level() {
+            return None;
+        }
+        Some(UPGRADE_COST * (level + 1))
+    }
+
+    pub fn buy(&mut self, upgrade: &Upgrade) -> bool {
+        let cost = match self.cost(upgrade) {
+            Some(c) if c <= self.credits  => c,
+            _                             => return false
+        };
+
+        self.credits -= cost;
+        match *upgrade {
+            Upgrade::Thrust     => self.thrust += 1,
+            Upgrade::Rotation   => self.rotation += 1,
+            Upgrade::FireRate   => self.fire_rate += 1,
+            Upgrade::Shield     => self.shield += 1,
+            Upgrade::Hull       => self.hull += 1,
+            Upgrade::Weapon     => self.weapon += 1
+        }
+        true
+    }
+
+    pub fn get_stats(&self) -> ShipStats {
+        let base = ShipStats::new();
+        ShipStats {
+            acc: base.acc + 0.15 * self.thrust as f32,
+            rotation_speed: base.rotation_speed + 0.75 * self.rotation as f32,
+            fire_cooldown: base.fire_cooldown - 3 * self.fire_rate as i32,
+            shield_time: base.shield_time + 45 * self.shield,
+            hull: base.hull + 25.0 * self.hull as f32,
+            guns: base.guns + self.weapon
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let options: Vec<String> = Upgrade::all().iter().enumerate().map(|(i, u)| {
+            match self.cost(u) {
+                Some(c) => format!("{} {} lv{} ({})", i + 1, u.name(), self.level(u), c),
+                None    => format!("{} {} lv{} (max)", i + 1, u.name(), self.level(u))
+            }
+        }).collect();
+
+        format!("credits [{}] - {} - enter to launch", self.credits, options.join(" - "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn each_level_costs_more_until_its_maxed(){
+        let mut profile = Profile::new();
+        profile.credits = 10000;
+
+        for level in 0..MAX_WEAPON_LEVEL {
+            assert_eq!(profile.cost(&Upgrade::Weapon), Some(UPGRADE_COST * (level + 1)));
+            assert!(profile.buy(&Upgrade::Weapon));
+        }
+        assert_eq!(profile.cost(&Upgrade::Weapon), None);
+        assert!(!profile.buy(&Upgrade::Weapon));
+
+        assert_eq!(profile.level(&Upgrade::Weapon), MAX_WEAPON_LEVEL);
+        assert_eq!(profile.credits, 10000 - UPGRADE_COST * 3);
+    }
+
+    #[test]
+    fn no_upgrade_without_the_credits(){
+        let mut profile = Profile::new();
+        profile.credits = UPGRADE_COST - 1;
+        assert!(!profile.buy(&Upgrade::Hull));
+        assert_eq!((profile.credits, profile.level(&Upgrade::Hull)), (UPGRADE_COST - 1, 0));
+
+        profile.credits = UPGRADE_COST;
+        assert!(profile.buy(&Upgrade::Hull));
+        assert_eq!((profile.credits, profile.level(&Upgrade::Hull)), (0, 1));
+        assert_eq!(profile.get_stats().hull, ShipStats::new().hull + 25.0);
+    }
+
+    #[test]
+    fn a_profile_survives_a_save_and_load(){
+        let path = env::temp_dir().join(format!("rusteroids-{}-a_profile_survives_a_save_and_load.profile", process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut profile = Profile::new();
+        profile.credits = UPGRADE_COST * 10;
+        for upgrade in Upgrade::all().iter() {
+            profile.buy(upgrade);
+        }
+        profile.buy(&Upgrade::Thrust);
+        profile.save(path).unwrap();
+
+        assert_eq!(Profile::load(path), profile);
+        assert_eq!(Profile::load("no such file"), Profile::new());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+use actor;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use agent;
+use messages::GameInstructions;
+use messages::PlayerInstructions;
+
+static TICKS_PER_SECOND: f32 = 60.0;
+static MIN_LEG: f32 = 2500.0;
+static MAX_LEG: f32 = 5000.0;
+
+pub struct Race{
+    pub seed: u64,
+    checkpoints: Vec<(i32, i32)>,
+    ticks: usize,
+    splits: Vec<usize>,
+    trace: Vec<(f32, f32, f32)>,
+    // what the player's ship was told to do, tick by tick
+    inputs: Vec<Vec<PlayerInstructions>>,
+    best_splits: Option<Vec<usize>>,
+    best_trace: Vec<(f32, f32, f32)>,
+    best_inputs: Vec<Vec<PlayerInstructions>>,
+    finished: bool
+}
+
+impl Race{
+    // a chain of checkpoints, each leg heading off from the last
+    // the same seed always lays out the same course
+    pub fn new(seed: u64, length: usize) -> Race {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut checkpoints = vec!();
+        let (mut x, mut y) = (0.0f32, 0.0f32);
+
+        for _ in 0..length {
+            let angle = rng.gen_range(0.0f32, 6.283);
+            let leg = rng.gen_range(MIN_LEG, MAX_LEG);
+            x += angle.sin() * leg;
+            y += angle.cos() * leg;
+            checkpoints.push((x as i32, y as i32));
+        }
+
+        Race {
+            seed: seed,
+            checkpoints: checkpoints,
+            ticks: 0,
+            splits: vec!(),
+            trace: vec!(),
+            inputs: vec!(),
+            best_splits: None,
+            best_trace: vec!(),
+            best_inputs: vec!(),
+            finished: false
+        }
+    }
+
+    pub fn get_checkpoints(&self) -> Vec<(i32, i32)> {
+        self.checkpoints.clone()
+    }
+
+    pub fn restart(&mut self){
+        self.ticks = 0;
+        self.splits = vec!();
+        self.trace = vec!();
+        self.inputs = vec!();
+        self.finished = false;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn update(&mut self, player: Option<&actor::ActorView>, input: &Vec<PlayerInstructions>){
+        if self.finished {
+            return;
+        }
+
+        self.ticks += 1;
+        self.inputs.push(input.clone());
+        match player {
+            Some(p) => self.trace.push((p.x, p.y, p.rotation)),
+            None    => ()
+        }
+    }
+
+    pub fn process_messages(&mut self, messages: &Vec<(GameInstructions, actor::ActorView)>){
+        for &(ref msg, ref v) in messages.iter(){
+            match msg{
+                &GameInstructions::Collect(_) => {
+                    if v.id == 1 && !self.finished {
+                        self.splits.push(self.ticks);
+                        if self.splits.len() == self.checkpoints.len() {
+                            self.finish();
+                        }
+                    }
+                },
+                _       => ()
+            }
+        }
+    }
+
+    // where the best run was at this point in the current run
+    pub fn get_ghost(&self) -> Option<(f32, f32, f32)> {
+        if self.best_trace.is_empty() {
+            return None;
+        }
+
+        let i = if self.ticks < self.best_trace.len() { self.ticks } else { self.best_trace.len() - 1 };
+        Some(self.best_trace[i])
+    }
+
+    // flies the best run again - the course and the world are the same
+    // every attempt, so the same input takes the same line
+    pub fn get_replay(&self) -> Option<agent::Replay> {
+        match self.best_splits {
+            Some(_) => Some(agent::Replay::new(1, self.best_inputs.clone())),
+            None    => None
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        if self.finished {
+            let best = match self.best_splits {
+                Some(ref best) => Race::seconds(best[best.len() - 1]),
+                None           => Race::seconds(self.ticks)
+            };
+            let splits: Vec<String> = self.splits.iter().map(|&s| Race::seconds(s)).collect();
+            return format!("race finished - time [{}] - best [{}] - splits {}", Race::seconds(self.ticks), best, splits.join(" "));
+        }
+
+        let mut summary = format!("race [{}] - checkpoint {}/{}", Race::seconds(self.ticks), self.splits.len(), self.checkpoints.len());
+
+        // compare the last split against the same checkpoint on the best run
+        match (self.splits.last(), self.best_splits.as_ref()) {
+            (Some(&split), Some(best)) => {
+                let diff = split as f32 - best[self.splits.len() - 1] as f32;
+                summary = format!("{} ({:+.2})", summary, diff / TICKS_PER_SECOND);
+            },
+            _ => ()
+        }
+
+        summary
+    }
+
+    fn finish(&mut self){
+        self.finished = true;
+
+        let is_best = match self.best_splits {
+            Some(ref best) => self.ticks < best[best.len() - 1],
+            None           => true
+        };
+
+        if is_best {
+            self.best_splits = Some(self.splits.clone());
+            self.best_trace = self.trace.clone();
+            self.best_inputs = self.inputs.clone();
+        }
+    }
+
+    fn seconds(ticks: usize) -> String {
+        format!("{:.2}s", ticks as f32 / TICKS_PER_SECOND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actor::Actor;
+    use agent::Agent;
+    use spaceship::Spaceship;
+
+    #[test]
+    fn the_best_run_comes_back_as_a_replay(){
+        let mut race = Race::new(1, 1);
+        let player = Spaceship::new(1, 0, 0, 0.0).get_view();
+        assert!(race.get_replay().is_none());
+
+        race.update(Some(&player), &vec!(PlayerInstructions::BeginIncreaseThrottle));
+        race.update(Some(&player), &vec!());
+        race.update(Some(&player), &vec!(PlayerInstructions::Fire));
+        race.process_messages(&vec!((GameInstructions::Collect(5), player.clone())));
+
+        let mut replay = race.get_replay().unwrap();
+        let surroundings = agent::Surroundings { nearby: &vec!(), collectables: &vec!(), input: &vec!(), order: None, avoidance: false };
+        let mut frames = vec!();
+        for _ in 0..3 {
+            let mut messages = vec!();
+            replay.control(&player, &surroundings, &mut messages);
+            frames.push(messages);
+        }
+        assert_eq!(frames, vec!(vec!((1, PlayerInstructions::BeginIncreaseThrottle)), vec!(), vec!((1, PlayerInstructions::Fire))));
+        assert!(replay.finished());
+    }
+
+    #[test]
+    fn a_slower_run_keeps_the_old_replay(){
+        let mut race = Race::new(1, 1);
+        let player = Spaceship::new(1, 0, 0, 0.0).get_view();
+        race.update(Some(&player), &vec!(PlayerInstructions::Fire));
+        race.process_messages(&vec!((GameInstructions::Collect(5), player.clone())));
+
+        race.restart();
+        for _ in 0..5 {
+            race.update(Some(&player), &vec!(PlayerInstructions::DropMine));
+        }
+        race.process_messages(&vec!((GameInstructions::Collect(5), player.clone())));
+
+        assert!(!race.get_replay().unwrap().finished());
+        assert_eq!(race.best_inputs, vec!(vec!(PlayerInstructions::Fire)));
+    }
+}
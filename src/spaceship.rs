@@ -3,16 +3,60 @@ use actor::ActorView;
 use actor;
 use messages::PlayerInstructions;
 use messages::GameInstructions;
-use rand;
+use faction::Faction;
+use loot::Loot;
+use loot::LootTable;
 use rand::Rng;
+use dice::Dice;
 
 static PI : f32 = 3.14159265359;
 static SHIELD_TIME: usize = 180;
 static SHOW_TRAILS: bool = false;
+static HYPERSPACE_COOLDOWN: usize = 600;
+static MALFUNCTION_CHANCE: u32 = 8; // percent
+static SHIELD_HIT_COST: usize = 30;
+// a tick clear of anything ends the contact - the next hit is a new one
+static SHIELD_CONTACT_TIME: usize = 2;
+static GUN_SPREAD: f32 = 0.08;
+pub static DRAG: f32 = 0.992;
+static RAPID_FIRE_TIME: usize = 300;
+static PARTS_PER_GUN: usize = 3;
+static MAX_GUNS: usize = 3;
+static HULL: f32 = 100.0;
+static RAM_DAMAGE: f32 = 25.0;
+// ticks after a hit before the next one counts, so an overlap isn't a hit every tick
+static INVULNERABLE_TIME: usize = 30;
+static DAMAGED: f32 = 0.66;
+static WRECKED: f32 = 0.33;
+
+// everything about a ship's handling that can be upgraded
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShipStats{
+    pub acc: f32,
+    pub rotation_speed: f32,
+    pub fire_cooldown: i32,
+    pub shield_time: usize,
+    pub hull: f32,
+    pub guns: usize
+}
+
+impl ShipStats{
+    pub fn new() -> ShipStats {
+        ShipStats {
+            acc: 1.05,
+            rotation_speed: 5.0,
+            fire_cooldown: 20,
+            shield_time: SHIELD_TIME,
+            hull: HULL,
+            guns: 1
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Spaceship{
     id: i32,
+    faction: Faction,
     x: f32,
     y: f32,
     acc_x: f32,
@@ -24,18 +68,37 @@ pub struct Spaceship{
     is_rotating_left: bool,
     shape: Vec<f32>,
     acc: f32,
+    rotation_speed: f32,
+    fire_cooldown: i32,
+    hull: f32,
+    hull_max: f32,
+    invulnerable: usize,
+    guns: usize,
+    weapon_parts: usize,
+    rapid_fire: usize,
+    drag: f32,
     is_alive:bool,
     color: Vec<f32>,
     shield: bool,
     normal_color : Vec<f32>,
     normal_shape : Vec<f32>,
+    base_color: Vec<f32>,
+    intact_shape: Vec<f32>,
+    wrecked_shape: Vec<f32>,
     fire_countdown: i32,
+    mine_countdown: i32,
+    hyperspace_countdown: usize,
     shield_timer: usize,
     shield_max_time: usize,
+    shield_contact: usize,
+    // rolled for malfunctions, smoke and flicker
+    dice: Dice,
     secondary_shape: Vec<f32>,
     secondary_shape_1: Vec<f32>,
     secondary_shape_2: Vec<f32>,
     secondary_color: Vec<f32>,
+    bubble_shape: Vec<f32>,
+    bubble_color: Vec<f32>,
     thrust_timer: usize
 }
 
@@ -73,36 +136,155 @@ impl Spaceship{
 
         let secondary_color = vec!(1.0, 1.0, 0.7);
 
-        let acc = 1.05;
+        // a ring around the ship while the shield is up
+        let segments = 16;
+        let (inner, outer) = (0.06, 0.07);
+        let mut bubble_shape = vec!();
+        for i in 0..segments {
+            let a1 = i as f32 * 2.0 * PI / segments as f32;
+            let a2 = (i + 1) as f32 * 2.0 * PI / segments as f32;
+            bubble_shape.extend(vec!(
+                a1.sin() * inner, a1.cos() * inner,
+                a1.sin() * outer, a1.cos() * outer,
+                a2.sin() * outer, a2.cos() * outer,
+
+                a2.sin() * outer, a2.cos() * outer,
+                a2.sin() * inner, a2.cos() * inner,
+                a1.sin() * inner, a1.cos() * inner
+            ).into_iter());
+        }
+
+        // one wing torn off
+        let wrecked_shape = vec!(
+            0.0, 0.05,
+            0.015, -0.03,
+            0.0, -0.025,
+
+            0.0, -0.025,
+            -0.025, -0.05,
+            0.0, 0.05
+        );
+
+        let stats = ShipStats::new();
 
         let color = vec!(0.5, 0.2, 0.2);
 
         Spaceship{
-            id: id, x: x as f32, y: y as f32,
+            id: id, faction: Faction::Pirates, x: x as f32, y: y as f32,
             rotation: rotation, acc_x: 0.0, acc_y: 0.0,
             is_accelerating: false, is_decelerating: false,
             is_rotating_right: false, is_rotating_left: false,
             shape: shape.clone(),
             normal_shape: shape.clone(),
-            acc: acc,
+            acc: stats.acc,
+            rotation_speed: stats.rotation_speed,
+            fire_cooldown: stats.fire_cooldown,
+            hull: stats.hull,
+            hull_max: stats.hull,
+            invulnerable: 0,
+            guns: stats.guns,
+            weapon_parts: 0,
+            rapid_fire: 0,
+            drag: DRAG,
             is_alive: true,
             color: color.clone(),
             normal_color: color.clone(),
+            base_color: color.clone(),
+            intact_shape: shape.clone(),
+            wrecked_shape: wrecked_shape,
             shield: false,
             fire_countdown: 0,
-            shield_timer: SHIELD_TIME,
-            shield_max_time: SHIELD_TIME,
+            mine_countdown: 0,
+            hyperspace_countdown: 0,
+            shield_timer: stats.shield_time,
+            shield_max_time: stats.shield_time,
+            shield_contact: 0,
+            dice: Dice::new(id as u64),
             secondary_color: secondary_color,
             secondary_shape: secondary_shape.clone(),
             secondary_shape_1: secondary_shape.clone(),
             secondary_shape_2: secondary_shape2,
+            bubble_shape: bubble_shape,
+            bubble_color: vec!(0.6, 0.85, 0.9),
             thrust_timer: 0
         }
     }
 
     pub fn set_color(&mut self, c: Vec<f32>){
-        self.normal_color = c.clone();
-        self.color = c;
+        self.base_color = c;
+        self.damage_state();
+    }
+
+    // darker as it takes damage, and missing a wing near the end
+    fn damage_state(&mut self){
+        let health = self.hull / self.hull_max;
+        let (shape, shade) = if health < WRECKED {
+            (self.wrecked_shape.clone(), 0.5)
+        } else if health < DAMAGED {
+            (self.intact_shape.clone(), 0.75)
+        } else {
+            (self.intact_shape.clone(), 1.0)
+        };
+
+        self.normal_shape = shape;
+        self.normal_color = self.base_color.iter().map(|c| c * shade).collect();
+
+        if !self.shield {
+            self.shape = self.normal_shape.clone();
+            self.color = self.normal_color.clone();
+        }
+    }
+
+    pub fn set_stats(&mut self, stats: &ShipStats){
+        self.acc = stats.acc;
+        self.rotation_speed = stats.rotation_speed;
+        self.fire_cooldown = stats.fire_cooldown;
+        self.shield_timer = stats.shield_time;
+        self.shield_max_time = stats.shield_time;
+        self.hull = stats.hull;
+        self.hull_max = stats.hull;
+        self.guns = stats.guns;
+        self.damage_state();
+    }
+
+    // closer to 1.0 and ships drift further before coming to a stop
+    pub fn set_drag(&mut self, drag: f32){
+        self.drag = drag;
+    }
+
+    pub fn set_dice(&mut self, dice: Dice){
+        self.dice = dice;
+    }
+
+    pub fn set_faction(&mut self, f: Faction){
+        self.set_color(f.color());
+        self.faction = f;
+    }
+
+    pub fn get_faction(&self) -> &Faction {
+        &self.faction
+    }
+
+    pub fn teleport(&mut self, x: f32, y: f32){
+        self.x = x;
+        self.y = y;
+    }
+
+    fn hyperspace(&mut self, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        if self.hyperspace_countdown > 0 || self.shield {
+            return;
+        }
+
+        self.hyperspace_countdown = HYPERSPACE_COOLDOWN;
+
+        if self.dice.gen_range(0u32, 100) < MALFUNCTION_CHANCE {
+            // the drive gives out and takes the ship with it
+            self.is_alive = false;
+            output_messages.push((GameInstructions::Explode, self.get_view().clone()));
+            return;
+        }
+
+        output_messages.push((GameInstructions::Hyperspace, self.get_view().clone()));
     }
 
     fn begin_increase_throttle(&mut self){
@@ -160,8 +342,8 @@ impl Spaceship{
     fn control(&mut self){
         if self.is_accelerating {
             self.accelerate();
-            let r1 = rand::thread_rng().gen_range(0.8f32, 1.0);
-            let r2 = rand::thread_rng().gen_range(0.0f32, 1.0);
+            let r1 = self.dice.gen_range(0.8f32, 1.0);
+            let r2 = self.dice.gen_range(0.0f32, 1.0);
             self.secondary_color = vec!(r1, r1, r2);
         }
 
@@ -180,8 +362,8 @@ impl Spaceship{
 
     fn slow_down(&mut self){
 
-        self.acc_x *= 0.992;
-        self.acc_y *= 0.992;
+        self.acc_x *= self.drag;
+        self.acc_y *= self.drag;
 
         if self.acc_x < 0.005 && self.acc_x > -0.005 {
             self.acc_x = 0.0;
@@ -193,7 +375,7 @@ impl Spaceship{
     }
 
     fn rotate(&mut self, direction : i32){
-        self.rotation += (direction * 5) as f32;
+        self.rotation += direction as f32 * self.rotation_speed;
     }
 
     fn get_rotate_vec(&mut self) -> (f32, f32){
@@ -225,6 +407,76 @@ impl Spaceship{
         self.is_rotating_right = false;;
     }
 
+    // every hit on the shield costs more than just holding it up - but
+    // something resting against it only costs once
+    fn shield_hit(&mut self){
+        if self.shield_contact == 0 {
+            self.shield_timer = self.shield_timer.saturating_sub(SHIELD_HIT_COST);
+            self.bubble_color = vec!(1.0, 1.0, 1.0);
+        }
+        self.shield_contact = SHIELD_CONTACT_TIME;
+    }
+
+    fn fire(&mut self, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        if self.fire_countdown > 0 || self.shield {
+            return;
+        }
+
+        // extra guns fan out either side of the nose
+        let view = self.get_view();
+        for i in 0..self.guns {
+            let mut shot = view.clone();
+            shot.rotation += (i as f32 - (self.guns - 1) as f32 / 2.0) * GUN_SPREAD;
+            output_messages.push((GameInstructions::Fire, shot));
+        }
+        self.fire_countdown = if self.rapid_fire > 0 { self.fire_cooldown / 2 } else { self.fire_cooldown };
+    }
+
+    fn hit(&mut self, damage: f32, output_messages:&mut Vec<(GameInstructions, ActorView)>){
+        if self.invulnerable > 0 {
+            return;
+        }
+
+        self.hull -= damage;
+        self.invulnerable = INVULNERABLE_TIME;
+        self.damage_state();
+        output_messages.push((GameInstructions::Hit, self.get_view().clone()));
+
+        if self.hull <= 0.0 {
+            self.is_alive = false;
+            output_messages.push((GameInstructions::Explode, self.get_view().clone()));
+
+            match self.get_loot_table() {
+                Some(table) => output_messages.push((GameInstructions::DropLoot(table), self.get_view().clone())),
+                None        => ()
+            }
+        }
+    }
+
+    fn get_loot_table(&self) -> Option<LootTable> {
+        match self.faction {
+            Faction::Pirates    => Some(LootTable::Pirate),
+            Faction::Police     => Some(LootTable::Police),
+            Faction::Traders    => Some(LootTable::Trader),
+            _                   => None
+        }
+    }
+
+    fn take_loot(&mut self, loot: &Loot){
+        match *loot {
+            Loot::Ammo          => self.rapid_fire = RAPID_FIRE_TIME,
+            Loot::ShieldCharge  => self.shield_timer = self.shield_max_time,
+            Loot::WeaponPart    => {
+                self.weapon_parts += 1;
+                if self.weapon_parts >= PARTS_PER_GUN && self.guns < MAX_GUNS {
+                    self.weapon_parts = 0;
+                    self.guns += 1;
+                }
+            },
+            Loot::Credits(_)    => ()
+        }
+    }
+
     fn shield_down(&mut self){
         self.shield = false;
         self.color = self.normal_color.clone();
@@ -247,6 +499,26 @@ impl Actor for Spaceship{
             self.fire_countdown -= 1;
         }
 
+        if self.mine_countdown > 0 {
+            self.mine_countdown -= 1;
+        }
+
+        if self.hyperspace_countdown > 0 {
+            self.hyperspace_countdown -= 1;
+        }
+
+        if self.rapid_fire > 0 {
+            self.rapid_fire -= 1;
+        }
+
+        if self.invulnerable > 0 {
+            self.invulnerable -= 1;
+        }
+
+        if self.shield_contact > 0 {
+            self.shield_contact -= 1;
+        }
+
         if !self.is_accelerating {
             self.thrust_timer += 1;
         }
@@ -254,9 +526,10 @@ impl Actor for Spaceship{
         if self.shield {
             if self.shield_timer > 0 {
 
-                let r = rand::thread_rng().gen_range(0.5f32, 1.0);
-                let b = rand::thread_rng().gen_range(0.2f32, 0.8);
+                let r = self.dice.gen_range(0.5f32, 1.0);
+                let b = self.dice.gen_range(0.2f32, 0.8);
                 self.color = vec!(r, 0.85, b);
+                self.bubble_color = vec!(0.4, 0.6 + r * 0.2, 0.9);
                 self.shield_timer -= 1;
             } else {
                 self.shield_down();
@@ -269,12 +542,17 @@ impl Actor for Spaceship{
         }
         self.control();
 
+        // trailing smoke once it's badly hurt
+        if self.hull / self.hull_max < WRECKED && self.dice.gen_range(0u32, 6) == 0 {
+            output_messages.push((GameInstructions::Trail, self.get_view().clone()));
+        }
+
         if !SHOW_TRAILS{
             return;
         }
 
         if self.is_accelerating {
-            if rand::thread_rng().gen_range(0u32, 10) == 9 {
+            if self.dice.gen_range(0u32, 10) == 9 {
                 output_messages.push((GameInstructions::Trail, self.get_view().clone()));
             }
         }
@@ -285,18 +563,24 @@ impl Actor for Spaceship{
         ActorView {
             id: self.id,
             parent: 0,
+            faction: self.faction.clone(),
             x: self.x,
             y: self.y,
+            vx: self.acc_x,
+            vy: self.acc_y,
             width: 50.0,
             height: 100.0,
             rotation: (self.rotation * PI) / 180.0,
             shape: self.shape.clone(),
             color: self.color.clone(),
-            collision_type: actor::CollisionType::Collide,
-            show_secondary: self.is_accelerating || self.thrust_timer < 5,
-            secondary_shape: Some(self.secondary_shape.clone()),
-            secondary_color: Some(self.secondary_color.clone()),
-            meter: self.shield_timer as f32 / self.shield_max_time as f32
+            collision_type: if self.shield { actor::CollisionType::Shield } else { actor::CollisionType::Collide },
+            show_secondary: self.shield || self.is_accelerating || self.thrust_timer < 5,
+            secondary_shape: Some(if self.shield { self.bubble_shape.clone() } else { self.secondary_shape.clone() }),
+            secondary_color: Some(if self.shield { self.bubble_color.clone() } else { self.secondary_color.clone() }),
+            meter: if self.shield_max_time > 0 { self.shield_timer as f32 / self.shield_max_time as f32 } else { 0.0 },
+            cooldown: self.hyperspace_countdown as f32 / HYPERSPACE_COOLDOWN as f32,
+            damage: RAM_DAMAGE,
+            hull: self.hull / self.hull_max
         }
     }
 
@@ -311,21 +595,34 @@ impl Actor for Spaceship{
             &PlayerInstructions::BeginRotateLeft         => self.begin_rotate_left(),
             &PlayerInstructions::StopRotateRight         => self.stop_rotate_right(),
             &PlayerInstructions::StopRotateLeft          => self.stop_rotate_left(),
-            &PlayerInstructions::Fire                      => {
-                                            if self.fire_countdown == 0 && !self.shield {
-                                                output_messages.push((GameInstructions::Fire, self.get_view().clone()));
-                                                self.fire_countdown = 20;
+            &PlayerInstructions::Fire                      => self.fire(output_messages),
+            &PlayerInstructions::Collide(damage)
+            | &PlayerInstructions::Deflect(_, _, _, _, damage) => {
+                                            if self.shield {
+                                                self.shield_hit();
+                                            } else {
+                                                self.hit(damage, output_messages);
                                             }
                                         },
-            &PlayerInstructions::Collide                   => {
+            &PlayerInstructions::Collect(token)            => output_messages.push((GameInstructions::Collect(token), self.get_view().clone())),
+            &PlayerInstructions::ShieldUp                 => self.shield_up(),
+            &PlayerInstructions::ShieldDown               => self.shield_down(),
+            &PlayerInstructions::Hyperspace               => self.hyperspace(output_messages),
+            // the jump never happened, so the drive's still charged
+            &PlayerInstructions::HyperspaceBlocked        => self.hyperspace_countdown = 0,
+            &PlayerInstructions::Loot(ref loot)           => self.take_loot(loot),
+            &PlayerInstructions::Tractor                  => {
                                             if !self.shield {
-                                                self.is_alive = false;
-                                                output_messages.push((GameInstructions::Explode, self.get_view().clone()));
+                                                output_messages.push((GameInstructions::Tractor, self.get_view().clone()));
                                             }
                                         },
-            &PlayerInstructions::Collect                   => output_messages.push((GameInstructions::Collect, self.get_view().clone())),
-            &PlayerInstructions::ShieldUp                 => self.shield_up(),
-            &PlayerInstructions::ShieldDown               => self.shield_down()
+            &PlayerInstructions::DropMine                 => {
+                                            if self.mine_countdown == 0 && !self.shield {
+                                                output_messages.push((GameInstructions::DropMine, self.get_view().clone()));
+                                                self.mine_countdown = 120;
+                                            }
+                                        },
+            _                                             => ()
         };
     }
 
@@ -333,6 +630,11 @@ impl Actor for Spaceship{
         self.is_alive = false;
     }
 
+    fn apply_impulse(&mut self, dvx: f32, dvy: f32){
+        self.acc_x += dvx;
+        self.acc_y += dvy;
+    }
+
     fn get_id(&self) -> i32{
         self.id
     }
@@ -342,3 +644,90 @@ impl Actor for Spaceship{
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(messages: &Vec<(GameInstructions, ActorView)>) -> usize {
+        messages.iter().filter(|&&(ref m, _)| match *m { GameInstructions::Hit => true, _ => false }).count()
+    }
+
+    #[test]
+    fn collide_takes_the_damage_it_carries(){
+        let mut ship = Spaceship::new(1, 0, 0, 0.0);
+        let mut out = vec!();
+        ship.execute(&PlayerInstructions::Collide(40.0), &mut out);
+
+        assert_eq!(ship.get_view().hull, 0.6);
+        assert_eq!(hits(&out), 1);
+    }
+
+    #[test]
+    fn deflect_damage_only_lands_without_the_shield(){
+        let mut ship = Spaceship::new(1, 0, 0, 0.0);
+        let mut out = vec!();
+        ship.execute(&PlayerInstructions::ShieldUp, &mut out);
+        ship.execute(&PlayerInstructions::Deflect(0.0, 0.0, 2, Faction::Unaligned, 40.0), &mut out);
+
+        assert_eq!(ship.get_view().hull, 1.0);
+        assert_eq!(hits(&out), 0);
+    }
+
+    #[test]
+    fn a_rock_resting_on_the_shield_only_costs_once(){
+        let mut ship = Spaceship::new(1, 0, 0, 0.0);
+        let mut out = vec!();
+        ship.execute(&PlayerInstructions::ShieldUp, &mut out);
+        let full = ship.shield_timer;
+
+        for _ in 0..60 {
+            ship.execute(&PlayerInstructions::Collide(10.0), &mut out);
+            ship.update(&mut out);
+        }
+        // one hit, plus a second of holding it up
+        assert_eq!(ship.shield_timer, full - SHIELD_HIT_COST - 60);
+
+        // back off for a moment and the next touch is a new hit
+        ship.update(&mut out);
+        ship.execute(&PlayerInstructions::Collide(10.0), &mut out);
+        assert_eq!(ship.shield_timer, full - 2 * SHIELD_HIT_COST - 61);
+    }
+
+    #[test]
+    fn a_blocked_jump_leaves_the_drive_charged(){
+        let mut ship = Spaceship::new(1, 0, 0, 0.0);
+        let mut out = vec!();
+        ship.hyperspace_countdown = HYPERSPACE_COOLDOWN;
+        ship.execute(&PlayerInstructions::HyperspaceBlocked, &mut out);
+
+        assert_eq!(ship.get_view().cooldown, 0.0);
+    }
+
+    #[test]
+    fn overlapping_hits_are_ignored_until_invulnerability_runs_out(){
+        let mut ship = Spaceship::new(1, 0, 0, 0.0);
+        let mut out = vec!();
+        ship.execute(&PlayerInstructions::Collide(10.0), &mut out);
+        for _ in 0..INVULNERABLE_TIME - 1 {
+            ship.update(&mut out);
+            ship.execute(&PlayerInstructions::Collide(10.0), &mut out);
+        }
+        assert_eq!(ship.get_view().hull, 0.9);
+
+        ship.update(&mut out);
+        ship.execute(&PlayerInstructions::Collide(10.0), &mut out);
+        assert_eq!(ship.get_view().hull, 0.8);
+        assert_eq!(hits(&out), 2);
+    }
+
+    #[test]
+    fn enough_damage_destroys_the_ship(){
+        let mut ship = Spaceship::new(2, 0, 0, 0.0);
+        let mut out = vec!();
+        ship.execute(&PlayerInstructions::Collide(HULL), &mut out);
+
+        assert!(!ship.is_alive());
+        assert!(out.iter().any(|&(ref m, _)| match *m { GameInstructions::Explode => true, _ => false }));
+    }
+}
@@ -1,98 +1,776 @@
 use actor::ActorView;
+use bullet;
+use actor::CollisionType;
+use faction::Faction;
 use messages::PlayerInstructions;
-use rand;
 use rand::Rng;
+use dice::Dice;
+use squadron::Order;
+use std::fs::File;
+use std::io::Read;
 
 static PI : f32 = 3.14159265359;
+static DEFAULT_TREES: &'static str = include_str!("../pilots.cfg");
+static ESCORT_DISTANCE: f32 = 600.0;
+static STRAFE_RANGE: f32 = 900.0;
+static STRAFE_BREAK: f32 = 400.0;
+static STRAFE_TIME: usize = 60;
+static PATROL_RANGE: i32 = 1500;
+static PATROL_ARRIVED: f32 = 300.0;
+static CRUISE_SPEED: f32 = 25.0;
+static FORMATION_SLACK: f32 = 2.0;
+static LEAD_SPEED: f32 = 15.0;
+static RIVAL_SPEED: f32 = 35.0;
+// ease off this far out so we don't sail past what we're after
+static ARRIVAL_BRAKING: f32 = 0.05;
+static ARRIVAL_SLACK: f32 = 2.0;
+static LOOKAHEAD: f32 = 60.0;
+static EMERGENCY_TIME: f32 = 12.0;
+static AVOID_MARGIN: f32 = 60.0;
+static MAX_AVOID_WEIGHT: f32 = 0.9;
+static SHIELD_LOW: f32 = 0.3;
+static FIRING_WINDOW: f32 = 20.0;
+// stop turning once closer than this, or we'd swing back and forth past the heading
+static TURN_DEADBAND: f32 = 3.0;
+static AIM_ERROR: f32 = 4.0;
+static AIM_TIME: usize = 30;
+static REACTION_DELAY: usize = 10;
+static DETECTION_RANGE: f32 = 2000.0;
+static HULL_LOW: f32 = 0.35;
 
-enum Activity {
-    Player(ActorView),
-    Nothing
+// the kinds of pilot - each flies its own tree
+#[derive(Clone, Debug, PartialEq)]
+pub enum Archetype {
+    Fighter,
+    Trader,
+    Minelayer,
+    // flies in a squadron and does what it's told
+    Wingman,
+    // races the player for the token
+    Rival
 }
 
-pub fn set_instructions(actor: ActorView,
-                        nearbys: Vec<ActorView>,
-                        player_messages:&mut Vec<(i32, PlayerInstructions)>){
+impl Archetype {
+    fn from_name(name: &str) -> Option<Archetype> {
+        match name {
+            "fighter"   => Some(Archetype::Fighter),
+            "trader"    => Some(Archetype::Trader),
+            "minelayer" => Some(Archetype::Minelayer),
+            "wingman"   => Some(Archetype::Wingman),
+            "rival"     => Some(Archetype::Rival),
+            _           => None
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    EnemyNear,
+    EnemyClose,
+    FriendNear,
+    ShieldLow,
+    ObstacleAhead,
+    InFormation,
+    SquadronAttacking,
+    TokenSeen
+}
+
+impl Condition {
+    fn from_name(name: &str) -> Option<Condition> {
+        match name {
+            "enemy_near"            => Some(Condition::EnemyNear),
+            "enemy_close"           => Some(Condition::EnemyClose),
+            "friend_near"           => Some(Condition::FriendNear),
+            "shield_low"            => Some(Condition::ShieldLow),
+            "obstacle_ahead"        => Some(Condition::ObstacleAhead),
+            "in_formation"          => Some(Condition::InFormation),
+            "squadron_attacking"    => Some(Condition::SquadronAttacking),
+            "token_seen"            => Some(Condition::TokenSeen),
+            _                       => None
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    Patrol,
+    Chase,
+    Strafe,
+    Flee,
+    Evade,
+    Escort,
+    LayMines,
+    Form,
+    Flank,
+    Collect
+}
+
+impl Action {
+    fn from_name(name: &str) -> Option<Action> {
+        match name {
+            "patrol"    => Some(Action::Patrol),
+            "chase"     => Some(Action::Chase),
+            "strafe"    => Some(Action::Strafe),
+            "flee"      => Some(Action::Flee),
+            "evade"     => Some(Action::Evade),
+            "escort"    => Some(Action::Escort),
+            "lay_mines" => Some(Action::LayMines),
+            "form"      => Some(Action::Form),
+            "flank"     => Some(Action::Flank),
+            "collect"   => Some(Action::Collect),
+            _           => None
+        }
+    }
+}
+
+// a behaviour tree - selectors try children until one doesn't fail,
+// sequences run children until one does
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Selector(Vec<Node>),
+    Sequence(Vec<Node>),
+    Check(Condition),
+    Do(Action)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    Success,
+    Failure,
+    Running
+}
+
+// a tree for each archetype, as laid out in pilots.cfg
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trees {
+    trees: Vec<(Archetype, Node)>
+}
+
+impl Trees {
+    // the trees in pilots.cfg, built in
+    pub fn new() -> Trees {
+        Trees::parse(DEFAULT_TREES).unwrap()
+    }
+
+    // falls back to the built in trees if the file is missing
+    pub fn load(path: &str) -> Result<Trees, String> {
+        let mut contents = String::new();
+
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            Ok(_)   => Trees::parse(&contents),
+            Err(_)  => Ok(Trees::new())
+        }
+    }
 
+    // an archetype on its own line, then its tree indented below it
+    pub fn parse(contents: &str) -> Result<Trees, String> {
+        let lines: Vec<(usize, usize, Vec<&str>)> = contents.lines().enumerate()
+            .filter(|&(_, line)| !line.trim().is_empty() && !line.trim().starts_with("#"))
+            .map(|(n, line)| (line.len() - line.trim_start().len(), n + 1, line.split_whitespace().collect()))
+            .collect();
 
-    // figure out priority
+        let mut trees = vec!();
+        let mut i = 0;
+        while i < lines.len() {
+            let (indent, n, ref words) = lines[i];
+            let archetype = match (indent, Archetype::from_name(words[0])) {
+                (0, Some(archetype)) => archetype,
+                (0, None)            => return Err(format!("line {}: no such archetype '{}'", n, words[0])),
+                _                    => return Err(format!("line {}: expected an archetype", n))
+            };
+            i += 1;
 
-    // 1. look for player
-    // 2. can go forward
-    // 3. fire
+            if i == lines.len() || lines[i].0 == 0 {
+                return Err(format!("line {}: '{}' has no tree", n, words[0]));
+            }
+            let root = Trees::parse_node(&lines, &mut i)?;
+            if i < lines.len() && lines[i].0 != 0 {
+                return Err(format!("line {}: a tree has only one root", lines[i].1));
+            }
+            trees.push((archetype, root));
+        }
 
-    let mut priority = Activity::Nothing;
+        for archetype in vec!(Archetype::Fighter, Archetype::Trader, Archetype::Minelayer, Archetype::Wingman, Archetype::Rival) {
+            if !trees.iter().any(|&(ref a, _)| *a == archetype) {
+                return Err(format!("no tree for {:?}", archetype));
+            }
+        }
 
-    for enemy in nearbys.into_iter() {
-        if enemy.id == 1 {
-            priority = Activity::Player(enemy);
-            break;
+        Ok(Trees { trees: trees })
+    }
+
+    // the node at lines[i] and everything indented below it
+    fn parse_node(lines: &Vec<(usize, usize, Vec<&str>)>, i: &mut usize) -> Result<Node, String> {
+        let (indent, n, ref words) = lines[*i];
+        *i += 1;
+
+        let mut children = vec!();
+        while *i < lines.len() && lines[*i].0 > indent {
+            children.push(Trees::parse_node(lines, i)?);
         }
 
+        match (words[0], children.is_empty()) {
+            ("selector", true) | ("sequence", true) => return Err(format!("line {}: '{}' needs children", n, words[0])),
+            ("check", false) | ("do", false)        => return Err(format!("line {}: '{}' can't have children", n, words[0])),
+            _                                       => ()
+        }
 
+        let arg = words.get(1).cloned().unwrap_or("");
+        Ok(match words[0] {
+            "selector" => Node::Selector(children),
+            "sequence" => Node::Sequence(children),
+            "check"    => match Condition::from_name(arg) {
+                Some(condition) => Node::Check(condition),
+                None            => return Err(format!("line {}: no such condition '{}'", n, arg))
+            },
+            "do"       => match Action::from_name(arg) {
+                Some(action) => Node::Do(action),
+                None         => return Err(format!("line {}: no such action '{}'", n, arg))
+            },
+            other      => return Err(format!("line {}: no such node '{}'", n, other))
+        })
     }
 
-    match priority{
-        Activity::Player(enemy)   => attack_player(actor, enemy, player_messages),
-        _               => random_behaviour(actor.id, player_messages)
+    pub fn get(&self, archetype: &Archetype) -> Node {
+        self.trees.iter().find(|&&(ref a, _)| a == archetype).map(|&(_, ref tree)| tree.clone()).unwrap()
     }
 }
 
-fn random_behaviour(id: i32, player_messages: &mut Vec<(i32, PlayerInstructions)>){
-    let rand = rand::thread_rng().gen_range(0u32, 100);
-    match rand {
-        0...50  => {
-            player_messages.push((id, PlayerInstructions::StopRotateRight));
-            player_messages.push((id, PlayerInstructions::StopRotateLeft));
-            player_messages.push((id, PlayerInstructions::BeginIncreaseThrottle));
-        },
-        51...70 => {
-            player_messages.push((id, PlayerInstructions::BeginRotateLeft));
-            player_messages.push((id, PlayerInstructions::StopRotateRight));
-            player_messages.push((id, PlayerInstructions::StopIncreaseThrottle));
-        },
-        71...90 => {
-            player_messages.push((id, PlayerInstructions::BeginRotateRight));
-            player_messages.push((id, PlayerInstructions::StopRotateLeft));
-            player_messages.push((id, PlayerInstructions::StopIncreaseThrottle));
+// what a pilot remembers from one tick to the next
+#[derive(Clone, Debug, PartialEq)]
+pub struct Memory {
+    waypoint: Option<(f32, f32)>,
+    strafe_timer: usize,
+    strafe_side: f32,
+    aim_offset: f32,
+    aim_timer: usize,
+    // how long the current enemy has been in sight, and which one it is
+    spotted: usize,
+    enemy: Option<i32>,
+    // rolled for waypoints and how far off a shot goes
+    dice: Dice
+}
+
+// how good a pilot is - the same for every pilot the manager hands out
+#[derive(Clone, Debug, PartialEq)]
+pub struct Skill {
+    // the most a shot will be off, in degrees
+    pub aim_error: f32,
+    // how far off we'll still open fire, in degrees
+    pub firing_window: f32,
+    // ticks an enemy has to be in sight before we do anything about it
+    pub reaction_delay: usize,
+    pub detection_range: f32
+}
+
+impl Skill {
+    pub fn new() -> Skill {
+        Skill {
+            aim_error: AIM_ERROR,
+            firing_window: FIRING_WINDOW,
+            reaction_delay: REACTION_DELAY,
+            detection_range: DETECTION_RANGE
         }
-        _      => {
-            player_messages.push((id, PlayerInstructions::StopRotateRight));
-            player_messages.push((id, PlayerInstructions::StopRotateLeft));
-            player_messages.push((id, PlayerInstructions::StopIncreaseThrottle));
+    }
+}
+
+// something we're on course to hit, and how soon
+#[derive(Clone, Debug, PartialEq)]
+pub struct Threat {
+    pub other: ActorView,
+    pub time: f32,
+    // where it will be relative to us at the closest point
+    pub miss: (f32, f32)
+}
+
+// everything a tree looks at, worked out once per tick
+pub struct Context {
+    pub actor: ActorView,
+    pub enemy: Option<ActorView>,
+    pub friend: Option<ActorView>,
+    pub threat: Option<Threat>,
+    // the nearest token, however far off
+    pub token: Option<ActorView>,
+    pub skill: Skill,
+    pub order: Option<Order>
+}
+
+impl Context {
+    pub fn new(actor: &ActorView, nearbys: &Vec<ActorView>, collectables: &Vec<ActorView>, skill: &Skill, order: Option<&Order>) -> Context {
+        let mut enemy: Option<ActorView> = None;
+        let mut friend = None;
+        let mut threat: Option<Threat> = None;
+
+        for other in nearbys.iter() {
+            if other.id == actor.id || !other.collision_type.is_solid() {
+                continue;
+            }
+
+            // anything solid we're heading for that we aren't trying to hit
+            if !actor.faction.is_hostile(&other.faction) {
+                match predict_collision(actor, other) {
+                    Some(t) => if threat.as_ref().map_or(true, |current| t.time < current.time) {
+                        threat = Some(t);
+                    },
+                    None    => ()
+                }
+            }
+
+            // only go after ships - not their bullets, mines or bits
+            if other.parent != 0 {
+                continue;
+            }
+
+            let d = distance(actor, other);
+            if actor.faction.is_hostile(&other.faction) {
+                if enemy.as_ref().map_or(true, |e| d < distance(actor, e)) {
+                    enemy = Some(other.clone());
+                }
+            } else if other.faction == Faction::Player && actor.faction.is_friendly(&other.faction) {
+                friend = Some(other.clone());
+            }
+        }
+
+        let token = collectables.iter().fold(None, |nearest: Option<&ActorView>, c| match nearest {
+            Some(n) if distance(actor, n) <= distance(actor, c) => Some(n),
+            _                                                   => Some(c)
+        });
+
+        Context {
+            actor: actor.clone(),
+            enemy: enemy,
+            friend: friend,
+            threat: threat,
+            token: token.cloned(),
+            skill: skill.clone(),
+            order: order.cloned()
         }
     }
 }
 
-fn attack_player(player: ActorView, enemy: ActorView, player_messages: &mut Vec<(i32, PlayerInstructions)>){
+// closest point of approach, assuming neither of us changes course
+pub fn predict_collision(actor: &ActorView, other: &ActorView) -> Option<Threat> {
+    let (px, py) = (other.x - actor.x, other.y - actor.y);
+    let (vx, vy) = (other.vx - actor.vx, other.vy - actor.vy);
+    let speed = vx * vx + vy * vy;
 
-    let dx = enemy.x - player.x;
-    let dy = enemy.y - player.y;
-    let mut ideal_rotation = dx.atan2(dy) * 180.0 / PI;
-    let mut player_rotation = player.rotation * 180.0 / PI;
+    let time = if speed > 0.0 { (-(px * vx + py * vy) / speed).max(0.0) } else { 0.0 };
+    if time > LOOKAHEAD {
+        return None;
+    }
 
+    let miss = (px + vx * time, py + vy * time);
+    let clearance = (actor.width + actor.height + other.width + other.height) / 2.0 + AVOID_MARGIN;
 
-    while ideal_rotation > 360.0 {
-        ideal_rotation -= 360.0;
+    if (miss.0 * miss.0 + miss.1 * miss.1).sqrt() > clearance {
+        return None;
     }
 
-    while ideal_rotation < -360.0 {
-        ideal_rotation += 360.0;
+    Some(Threat { other: other.clone(), time: time, miss: miss })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Thrust {
+    Always,
+    WhenAligned,
+    Never
+}
+
+// what the tree wants this tick - turned into instructions once
+// avoidance has had its say
+#[derive(Clone, Debug, PartialEq)]
+pub struct Steer {
+    pub heading: Option<f32>,
+    pub thrust: Thrust,
+    // open fire once we're within this many degrees of the heading
+    pub fire: Option<f32>,
+    pub mine: bool,
+    pub shield: bool,
+    pub avoid: bool,
+    // stop thrusting once we're going this fast
+    pub max_speed: Option<f32>
+}
+
+impl Steer {
+    pub fn new() -> Steer {
+        Steer { heading: None, thrust: Thrust::Never, fire: None, mine: false, shield: false, avoid: true, max_speed: None }
     }
 
-    while player_rotation > 360.0 {
-        player_rotation -= 360.0;
+    pub fn apply(&self, actor: &ActorView, window: f32, player_messages: &mut Vec<(i32, PlayerInstructions)>){
+        let off = match self.heading {
+            Some(heading) => turn_to(actor, heading, player_messages),
+            None          => {
+                player_messages.push((actor.id, PlayerInstructions::StopRotateLeft));
+                player_messages.push((actor.id, PlayerInstructions::StopRotateRight));
+                0.0
+            }
+        };
+        let aligned = off < window;
+
+        let thrust = match self.thrust {
+            Thrust::Always      => true,
+            Thrust::WhenAligned => aligned,
+            Thrust::Never       => false
+        } && self.max_speed.map_or(true, |max| (actor.vx * actor.vx + actor.vy * actor.vy).sqrt() < max);
+
+        if thrust {
+            player_messages.push((actor.id, PlayerInstructions::BeginIncreaseThrottle));
+        } else {
+            player_messages.push((actor.id, PlayerInstructions::StopIncreaseThrottle));
+        }
+
+        if self.fire.map_or(false, |fire_window| off < fire_window) {
+            player_messages.push((actor.id, PlayerInstructions::Fire));
+        }
+
+        if self.mine {
+            player_messages.push((actor.id, PlayerInstructions::DropMine));
+        }
+
+        if self.shield {
+            player_messages.push((actor.id, PlayerInstructions::ShieldUp));
+        }
     }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pilot {
+    pub id: i32,
+    tree: Node,
+    pub memory: Memory,
+    pub skill: Skill
+}
 
-    while player_rotation < -360.0 {
-        player_rotation += 360.0;
+impl Pilot {
+    pub fn new(id: i32, tree: Node, skill: Skill, dice: Dice) -> Pilot {
+        Pilot {
+            id: id,
+            tree: tree,
+            memory: Memory {
+                waypoint: None,
+                strafe_timer: 0,
+                strafe_side: 1.0,
+                aim_offset: 0.0,
+                aim_timer: 0,
+                spotted: 0,
+                enemy: None,
+                dice: dice
+            },
+            skill: skill
+        }
     }
 
-    let d_rotation = ideal_rotation - player_rotation;
+    pub fn think(&mut self, actor: &ActorView, nearbys: &Vec<ActorView>, collectables: &Vec<ActorView>, avoidance: bool,
+                 order: Option<&Order>, player_messages: &mut Vec<(i32, PlayerInstructions)>){
+        let mut context = Context::new(actor, nearbys, collectables, &self.skill, order);
+
+        // nobody reacts instantly - not even to a new enemy turning up
+        // while they're still busy with the last one
+        let enemy = context.enemy.as_ref().map(|e| e.id);
+        if enemy.is_some() && enemy == self.memory.enemy {
+            self.memory.spotted += 1;
+        } else {
+            self.memory.spotted = if enemy.is_some() { 1 } else { 0 };
+        }
+        self.memory.enemy = enemy;
+        if self.memory.spotted <= self.skill.reaction_delay {
+            context.enemy = None;
+        }
+        let mut steer = Steer::new();
+
+        // the shield only goes up to take a hit we can't avoid
+        if actor.collision_type == CollisionType::Shield {
+            player_messages.push((actor.id, PlayerInstructions::ShieldDown));
+        }
 
-    if d_rotation < 20.0 && d_rotation > -20.0 {
-        player_messages.push((player.id, PlayerInstructions::BeginIncreaseThrottle));
-        player_messages.push((player.id, PlayerInstructions::Fire));
+        tick(&self.tree, &context, &mut self.memory, &mut steer);
+
+        if avoidance && steer.avoid {
+            avoid(&context, &mut steer);
+        }
+
+        steer.apply(actor, self.skill.firing_window, player_messages);
+    }
+}
+
+pub fn tick(node: &Node, context: &Context, memory: &mut Memory, steer: &mut Steer) -> Status {
+    match *node {
+        Node::Selector(ref children) => {
+            for child in children.iter() {
+                match tick(child, context, memory, steer) {
+                    Status::Failure => continue,
+                    status          => return status
+                }
+            }
+            Status::Failure
+        },
+        Node::Sequence(ref children) => {
+            let mut status = Status::Success;
+            for child in children.iter() {
+                status = tick(child, context, memory, steer);
+                if status == Status::Failure {
+                    return status;
+                }
+            }
+            status
+        },
+        Node::Check(ref condition) => if check(condition, context) { Status::Success } else { Status::Failure },
+        Node::Do(ref action) => act(action, context, memory, steer)
+    }
+}
+
+fn check(condition: &Condition, context: &Context) -> bool {
+    match *condition {
+        Condition::EnemyNear        => context.enemy.is_some(),
+        Condition::EnemyClose       => context.enemy.as_ref().map_or(false, |e| distance(&context.actor, e) < STRAFE_RANGE),
+        Condition::FriendNear       => context.friend.is_some(),
+        Condition::ShieldLow        => context.actor.meter < SHIELD_LOW || context.actor.hull < HULL_LOW,
+        Condition::ObstacleAhead    => context.threat.as_ref().map_or(false, |t| t.time < EMERGENCY_TIME),
+        Condition::InFormation      => match context.order { Some(Order::Form(..)) => true, _ => false },
+        Condition::SquadronAttacking => match context.order { Some(Order::Attack(..)) => true, _ => false },
+        Condition::TokenSeen        => context.token.is_some()
+    }
+}
+
+fn act(action: &Action, context: &Context, memory: &mut Memory, steer: &mut Steer) -> Status {
+    let actor = &context.actor;
+
+    // orders from the squadron
+    match (action, context.order.as_ref()) {
+        (&Action::Form, Some(&Order::Form(dvx, dvy)))               => {
+            form((dvx, dvy), steer);
+            return Status::Running;
+        },
+        (&Action::Flank, Some(&Order::Attack(ref target, flank)))   => {
+            flank_attack(actor, target, flank, &context.skill, memory, steer);
+            return Status::Running;
+        },
+        _                                                           => ()
+    }
+
+    match (action, context.token.as_ref()) {
+        (&Action::Collect, Some(token))                             => {
+            fly_to(actor, (token.x, token.y), RIVAL_SPEED, steer);
+            return Status::Running;
+        },
+        _                                                           => ()
+    }
+
+    match (action, context.enemy.as_ref(), context.friend.as_ref(), context.threat.as_ref()) {
+        (&Action::Chase, Some(enemy), _, _)         => attack(actor, enemy, &context.skill, memory, steer),
+        (&Action::Strafe, Some(enemy), _, _)        => strafe(actor, enemy, &context.skill, memory, steer),
+        (&Action::Flee, Some(enemy), _, _)          => flee(actor, enemy, steer),
+        (&Action::LayMines, Some(enemy), _, _)      => {
+            steer.mine = true;
+            flee(actor, enemy, steer);
+        },
+        (&Action::Escort, _, Some(friend), _)       => escort(actor, friend, steer),
+        (&Action::Evade, _, _, Some(threat))        => evade(actor, threat, steer),
+        (&Action::Patrol, _, _, _)                  => {
+            patrol(actor, memory, steer);
+            // leading a squadron - go easy so the wingmen can keep up
+            if context.order == Some(Order::Lead) {
+                steer.max_speed = Some(LEAD_SPEED);
+            }
+        },
+        _                                           => return Status::Failure
+    }
+
+    Status::Running
+}
+
+// fly between waypoints instead of rolling a new direction every tick
+fn patrol(actor: &ActorView, memory: &mut Memory, steer: &mut Steer){
+    let arrived = match memory.waypoint {
+        Some((x, y)) => ((x - actor.x).powi(2) + (y - actor.y).powi(2)).sqrt() < PATROL_ARRIVED,
+        None         => true
+    };
+
+    if arrived {
+        let x = actor.x + memory.dice.gen_range(-PATROL_RANGE, PATROL_RANGE) as f32;
+        let y = actor.y + memory.dice.gen_range(-PATROL_RANGE, PATROL_RANGE) as f32;
+        memory.waypoint = Some((x, y));
+    }
+
+    let (x, y) = memory.waypoint.unwrap();
+    steer.heading = Some((x - actor.x).atan2(y - actor.y) * 180.0 / PI);
+    steer.thrust = Thrust::WhenAligned;
+    steer.max_speed = Some(CRUISE_SPEED);
+}
+
+// match velocities with a point, slowing as we get there rather than overshooting
+pub fn fly_to(actor: &ActorView, (x, y): (f32, f32), speed: f32, steer: &mut Steer){
+    let d = ((x - actor.x).powi(2) + (y - actor.y).powi(2)).sqrt().max(1.0);
+    let speed = speed.min(d * ARRIVAL_BRAKING);
+    let dvx = (x - actor.x) / d * speed - actor.vx;
+    let dvy = (y - actor.y) / d * speed - actor.vy;
+
+    steer.heading = Some(dvx.atan2(dvy) * 180.0 / PI);
+    steer.thrust = if (dvx * dvx + dvy * dvy).sqrt() > ARRIVAL_SLACK { Thrust::WhenAligned } else { Thrust::Never };
+}
+
+// aim where they'll be, give or take however good a shot we are
+fn attack(player: &ActorView, enemy: &ActorView, skill: &Skill, memory: &mut Memory, steer: &mut Steer){
+    if memory.aim_timer == 0 {
+        memory.aim_timer = AIM_TIME;
+        memory.aim_offset = if skill.aim_error > 0.0 { memory.dice.gen_range(-skill.aim_error, skill.aim_error) } else { 0.0 };
+    }
+    memory.aim_timer -= 1;
+
+    steer.heading = Some(lead(player, enemy) + memory.aim_offset);
+    steer.thrust = Thrust::WhenAligned;
+    steer.fire = Some(target_window(player, enemy).max(TURN_DEADBAND).min(skill.firing_window));
+}
+
+// how far either side of its centre the target is, in degrees - any further
+// off than that and the shot goes wide
+fn target_window(player: &ActorView, target: &ActorView) -> f32 {
+    let radius = (target.width + target.height) / 4.0;
+    radius.atan2(distance(player, target)) * 180.0 / PI
+}
+
+// close in firing, then peel off to the side before coming round again
+fn strafe(player: &ActorView, enemy: &ActorView, skill: &Skill, memory: &mut Memory, steer: &mut Steer){
+    if memory.strafe_timer > 0 {
+        memory.strafe_timer -= 1;
+        steer.heading = Some(bearing(player, enemy) + 100.0 * memory.strafe_side);
+        steer.thrust = Thrust::Always;
+        return;
+    }
+
+    if distance(player, enemy) < STRAFE_BREAK {
+        memory.strafe_timer = STRAFE_TIME;
+        memory.strafe_side = -memory.strafe_side;
+    }
+
+    attack(player, enemy, skill, memory, steer);
+}
+
+fn flee(player: &ActorView, enemy: &ActorView, steer: &mut Steer){
+    // point directly away from the enemy
+    steer.heading = Some(bearing(enemy, player));
+    steer.thrust = Thrust::Always;
+}
+
+// too late to steer round it gently - hard turn away, shield up if it's about to hit
+fn evade(player: &ActorView, threat: &Threat, steer: &mut Steer){
+    steer.heading = Some(away_from(player, threat));
+    steer.thrust = Thrust::WhenAligned;
+    steer.shield = distance(player, &threat.other) < (player.width + player.height + threat.other.width + threat.other.height) / 2.0 + AVOID_MARGIN;
+    steer.avoid = false;
+}
+
+// steer by thrusting along the change in velocity the squadron asked for,
+// coasting once we're close enough
+fn form((dvx, dvy): (f32, f32), steer: &mut Steer){
+    if (dvx * dvx + dvy * dvy).sqrt() < FORMATION_SLACK {
+        return;
+    }
+
+    steer.heading = Some(dvx.atan2(dvy) * 180.0 / PI);
+    steer.thrust = Thrust::WhenAligned;
+}
+
+// swing out to the side on the way in, then strafe like anyone else
+fn flank_attack(player: &ActorView, target: &ActorView, flank: f32, skill: &Skill, memory: &mut Memory, steer: &mut Steer){
+    if flank != 0.0 && distance(player, target) > STRAFE_RANGE {
+        steer.heading = Some(bearing(player, target) + flank);
+        steer.thrust = Thrust::WhenAligned;
+        return;
+    }
+
+    strafe(player, target, skill, memory, steer);
+}
+
+fn escort(player: &ActorView, friend: &ActorView, steer: &mut Steer){
+    steer.heading = Some(bearing(player, friend));
+    steer.thrust = if distance(player, friend) > ESCORT_DISTANCE { Thrust::WhenAligned } else { Thrust::Never };
+}
+
+// bend whatever heading the tree picked away from the nearest threat -
+// the sooner it would hit, the harder we turn
+pub fn avoid(context: &Context, steer: &mut Steer){
+    let threat = match context.threat {
+        Some(ref threat) => threat,
+        None             => return
+    };
+
+    let weight = (1.0 - threat.time / LOOKAHEAD).max(0.0).min(MAX_AVOID_WEIGHT);
+    let goal = steer.heading.unwrap_or(context.actor.rotation * 180.0 / PI) * PI / 180.0;
+    let away = away_from(&context.actor, threat) * PI / 180.0;
+
+    let x = goal.sin() * (1.0 - weight) + away.sin() * weight;
+    let y = goal.cos() * (1.0 - weight) + away.cos() * weight;
+
+    steer.heading = Some(x.atan2(y) * 180.0 / PI);
+    // thrust is the only way to change course, so use it whatever the goal
+    steer.thrust = Thrust::WhenAligned;
+    steer.max_speed = None;
+}
+
+// the heading that takes us off the collision course
+fn away_from(actor: &ActorView, threat: &Threat) -> f32 {
+    let (mx, my) = threat.miss;
+    if mx == 0.0 && my == 0.0 {
+        // dead centre - pick a side
+        let (px, py) = (threat.other.x - actor.x, threat.other.y - actor.y);
+        return py.atan2(-px) * 180.0 / PI;
+    }
+    (-mx).atan2(-my) * 180.0 / PI
+}
+
+pub fn distance(a: &ActorView, b: &ActorView) -> f32 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    (dx * dx + dy * dy).sqrt()
+}
+
+// the heading, in degrees, that points from a to b
+pub fn bearing(a: &ActorView, b: &ActorView) -> f32 {
+    (b.x - a.x).atan2(b.y - a.y) * 180.0 / PI
+}
+
+// the heading, in degrees, that puts a bullet fired now on course to meet
+// the target - straight at it if it's too quick to catch
+pub fn lead(shooter: &ActorView, target: &ActorView) -> f32 {
+    let (px, py) = (target.x - shooter.x, target.y - shooter.y);
+    let (vx, vy) = (target.vx, target.vy);
+
+    // |p + v t| = bullet speed * t
+    let a = vx * vx + vy * vy - bullet::SPEED * bullet::SPEED;
+    let b = 2.0 * (px * vx + py * vy);
+    let c = px * px + py * py;
+
+    let time = if a.abs() < 0.001 {
+        if b < 0.0 { Some(-c / b) } else { None }
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            None
+        } else {
+            let root = discriminant.sqrt();
+            let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+            match (t1 > 0.0, t2 > 0.0) {
+                (true, true)    => Some(t1.min(t2)),
+                (true, false)   => Some(t1),
+                (false, true)   => Some(t2),
+                (false, false)  => None
+            }
+        }
+    };
+
+    match time {
+        Some(t) => (px + vx * t).atan2(py + vy * t) * 180.0 / PI,
+        None    => bearing(shooter, target)
+    }
+}
+
+// rotates towards ideal_rotation, returns how many degrees off it we still are
+// - keeps turning until we're right on it, so shots go where they're aimed
+fn turn_to(player: &ActorView, ideal_rotation: f32, player_messages: &mut Vec<(i32, PlayerInstructions)>) -> f32 {
+    let d_rotation = get_d_rotation(player, ideal_rotation);
+
+    if d_rotation.abs() < TURN_DEADBAND {
         player_messages.push((player.id, PlayerInstructions::StopRotateLeft));
         player_messages.push((player.id, PlayerInstructions::StopRotateRight));
     } else if d_rotation < 0.0 {
@@ -102,5 +780,171 @@ fn attack_player(player: ActorView, enemy: ActorView, player_messages: &mut Vec<
         player_messages.push((player.id, PlayerInstructions::StopRotateLeft));
         player_messages.push((player.id, PlayerInstructions::BeginRotateRight));
     }
+
+    d_rotation.abs()
+}
+
+// the shortest turn from where we're pointing to ideal_rotation, in degrees
+fn get_d_rotation(player: &ActorView, ideal_rotation: f32) -> f32 {
+    let mut d_rotation = ideal_rotation - player.rotation * 180.0 / PI;
+
+    while d_rotation > 180.0 {
+        d_rotation -= 360.0;
+    }
+
+    while d_rotation < -180.0 {
+        d_rotation += 360.0;
+    }
+
+    d_rotation
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actor::Actor;
+    use spaceship::Spaceship;
+
+    fn ship(x: f32, y: f32, vx: f32, vy: f32) -> ActorView {
+        let mut view = Spaceship::new(2, 0, 0, 0.0).get_view();
+        view.x = x;
+        view.y = y;
+        view.vx = vx;
+        view.vy = vy;
+        view
+    }
+
+    fn fired(messages: &Vec<(i32, PlayerInstructions)>) -> bool {
+        messages.iter().any(|&(_, ref m)| *m == PlayerInstructions::Fire)
+    }
+
+    #[test]
+    fn lead_puts_the_bullet_where_the_target_will_be(){
+        let shooter = ship(0.0, 0.0, 0.0, 0.0);
+        let target = ship(0.0, 1000.0, 30.0, 0.0);
+        let heading = lead(&shooter, &target) * PI / 180.0;
+
+        let closest = (0..400).map(|i| i as f32 * 0.05).map(|t| {
+            let (bx, by) = (heading.sin() * bullet::SPEED * t, heading.cos() * bullet::SPEED * t);
+            ((target.x + target.vx * t - bx).powi(2) + (target.y + target.vy * t - by).powi(2)).sqrt()
+        }).fold(f32::MAX, f32::min);
+        assert!(closest < 5.0, "missed by {}", closest);
+    }
+
+    #[test]
+    fn lead_aims_straight_at_what_it_cant_catch(){
+        let shooter = ship(0.0, 0.0, 0.0, 0.0);
+        let target = ship(500.0, 500.0, bullet::SPEED * 2.0, bullet::SPEED * 2.0);
+
+        assert_eq!(lead(&shooter, &target), bearing(&shooter, &target));
+    }
+
+    #[test]
+    fn predict_collision_spots_a_head_on(){
+        let actor = ship(0.0, 0.0, 0.0, 10.0);
+        let other = ship(0.0, 300.0, 0.0, -10.0);
+
+        let threat = predict_collision(&actor, &other).unwrap();
+        assert!((threat.time - 15.0).abs() < 0.01);
+        assert_eq!(threat.miss, (0.0, 0.0));
+    }
+
+    #[test]
+    fn predict_collision_ignores_near_misses_and_far_off_ones(){
+        let actor = ship(0.0, 0.0, 0.0, 10.0);
+
+        assert_eq!(predict_collision(&actor, &ship(2000.0, 300.0, 0.0, -10.0)), None);
+        assert_eq!(predict_collision(&actor, &ship(0.0, 300.0 * LOOKAHEAD, 0.0, -10.0)), None);
+        assert_eq!(predict_collision(&actor, &ship(0.0, -300.0, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn aligned_enough_to_thrust_isnt_aligned_enough_to_shoot(){
+        let player = ship(0.0, 0.0, 0.0, 0.0);
+        let enemy = ship(0.0, 2000.0, 0.0, 0.0);
+        let skill = Skill { aim_error: 0.0, .. Skill::new() };
+        let mut memory = Pilot::new(2, Trees::new().get(&Archetype::Fighter), skill.clone(), Dice::new(0)).memory;
+
+        let mut steer = Steer::new();
+        attack(&player, &enemy, &skill, &mut memory, &mut steer);
+        steer.heading = Some(10.0);
+        let mut messages = vec!();
+        steer.apply(&player, skill.firing_window, &mut messages);
+        assert!(messages.contains(&(2, PlayerInstructions::BeginIncreaseThrottle)));
+        assert!(!fired(&messages));
+
+        steer.heading = Some(1.0);
+        let mut messages = vec!();
+        steer.apply(&player, skill.firing_window, &mut messages);
+        assert!(fired(&messages));
+    }
+
+    #[test]
+    fn closer_targets_open_the_window(){
+        let player = ship(0.0, 0.0, 0.0, 0.0);
+
+        assert!(target_window(&player, &ship(0.0, 200.0, 0.0, 0.0)) > target_window(&player, &ship(0.0, 2000.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn pilots_cfg_lays_out_every_tree(){
+        let trees = Trees::new();
+        let evade = Node::Sequence(vec!(Node::Check(Condition::ObstacleAhead), Node::Do(Action::Evade)));
+
+        assert_eq!(trees.get(&Archetype::Trader), Node::Selector(vec!(
+            evade.clone(),
+            Node::Sequence(vec!(Node::Check(Condition::EnemyNear), Node::Do(Action::Flee))),
+            Node::Do(Action::Patrol)
+        )));
+        assert_eq!(trees.get(&Archetype::Fighter), Node::Selector(vec!(
+            evade,
+            Node::Sequence(vec!(Node::Check(Condition::EnemyNear), Node::Check(Condition::ShieldLow), Node::Do(Action::Flee))),
+            Node::Sequence(vec!(Node::Check(Condition::EnemyNear), Node::Selector(vec!(
+                Node::Sequence(vec!(Node::Check(Condition::EnemyClose), Node::Do(Action::Strafe))),
+                Node::Do(Action::Chase)
+            )))),
+            Node::Sequence(vec!(Node::Check(Condition::FriendNear), Node::Do(Action::Escort))),
+            Node::Do(Action::Patrol)
+        )));
+    }
+
+    #[test]
+    fn bad_trees_are_reported_with_their_line(){
+        let rest = "trader\n do patrol\nminelayer\n do patrol\nwingman\n do patrol\nrival\n do patrol\n";
+        let parse = |fighter: &str| Trees::parse(&format!("{}{}", fighter, rest)).err();
+
+        assert_eq!(parse("fighter\n do patrol\n"), None);
+        assert_eq!(parse("fighter\n  do dance\n"), Some("line 2: no such action 'dance'".to_string()));
+        assert_eq!(parse("fighter\n  selector\n"), Some("line 2: 'selector' needs children".to_string()));
+        assert_eq!(parse("fighter\n  do chase\n    do patrol\n"), Some("line 2: 'do' can't have children".to_string()));
+        assert_eq!(parse("fighter\n  do chase\n  do patrol\n"), Some("line 3: a tree has only one root".to_string()));
+        assert_eq!(parse("pirate\n  do chase\n"), Some("line 1: no such archetype 'pirate'".to_string()));
+        assert_eq!(Trees::parse(rest).err(), Some("no tree for Fighter".to_string()));
+    }
+
+    #[test]
+    fn a_new_enemy_has_to_be_spotted_all_over_again(){
+        let skill = Skill::new();
+        let mut pilot = Pilot::new(2, Trees::new().get(&Archetype::Fighter), skill.clone(), Dice::new(0));
+        let mut actor = ship(0.0, 0.0, 0.0, 0.0);
+        actor.faction = Faction::Police;
+        let mut first = ship(0.0, 1000.0, 0.0, 0.0);
+        first.id = 4;
+        first.faction = Faction::Pirates;
+        let mut second = first.clone();
+        second.id = 3;
+        second.y = 500.0;
+
+        for _ in 0..skill.reaction_delay + 1 {
+            pilot.think(&actor, &vec!(first.clone()), &vec!(), false, None, &mut vec!());
+        }
+        assert!(pilot.memory.spotted > skill.reaction_delay);
+
+        pilot.think(&actor, &vec!(first.clone(), second.clone()), &vec!(), false, None, &mut vec!());
+        assert_eq!(pilot.memory.spotted, 1);
+        assert_eq!(pilot.memory.enemy, Some(3));
+
+        pilot.think(&actor, &vec!(), &vec!(), false, None, &mut vec!());
+        assert_eq!(pilot.memory.spotted, 0);
+    }
+}
@@ -0,0 +1,251 @@
+use actor::ActorView;
+use faction::Faction;
+use rand::Rng;
+use dice::Dice;
+
+static SLOT_SPACING: f32 = 220.0;
+static SLOT_PULL: f32 = 0.01;
+static SEPARATION_RANGE: f32 = 180.0;
+static SEPARATION: f32 = 400.0;
+static ALIGNMENT: f32 = 0.3;
+static COHESION: f32 = 0.005;
+// how much further than they can see a target has to get to shake them off
+static DISENGAGE: f32 = 1.5;
+static PINCER_ANGLE: f32 = 70.0;
+static STRAFE_ANGLE: f32 = 15.0;
+
+// how the squadron goes about a fight
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tactic {
+    // split either side and come at the target from two directions
+    Pincer,
+    // line abreast, diving through and coming round again
+    StrafingRun
+}
+
+// what the squadron wants from one of its ships this tick
+#[derive(Clone, Debug, PartialEq)]
+pub enum Order {
+    // the leader flies its own course
+    Lead,
+    // the change in velocity that keeps us in our slot
+    Form(f32, f32),
+    // go after the target, swinging round by `flank` degrees on the way in
+    Attack(ActorView, f32)
+}
+
+// a group of ships that spawned together and fly as one - the first member leads,
+// and whoever's next in line takes over if the leader goes down
+#[derive(Clone, Debug, PartialEq)]
+pub struct Squadron {
+    pub members: Vec<i32>,
+    pub faction: Faction,
+    tactic: Tactic,
+    target: Option<i32>,
+    dice: Dice
+}
+
+impl Squadron {
+    pub fn new(members: Vec<i32>, faction: Faction, dice: Dice) -> Squadron {
+        Squadron {
+            members: members,
+            faction: faction,
+            tactic: Tactic::Pincer,
+            target: None,
+            dice: dice
+        }
+    }
+
+    // where the ith ship sits relative to the leader - a V behind it,
+    // alternating sides
+    pub fn slot(i: usize, (x, y): (f32, f32), rotation: f32) -> (f32, f32) {
+        let rank = ((i + 1) / 2) as f32;
+        let side = if i % 2 == 1 { 1.0 } else { -1.0 };
+        let (back, across) = (-rank * SLOT_SPACING, side * rank * SLOT_SPACING);
+
+        // along the nose is (sin r, cos r), across it is (cos r, -sin r)
+        (x + back * rotation.sin() + across * rotation.cos(),
+         y + back * rotation.cos() - across * rotation.sin())
+    }
+
+    // drop the dead, pick a fight if there's one to be had and hand out orders
+    pub fn update(&mut self, ships: &Vec<ActorView>, range: f32) -> Vec<(i32, Order)> {
+        self.members.retain(|id| ships.iter().any(|s| s.id == *id));
+
+        let squad: Vec<&ActorView> = self.members.iter()
+            .filter_map(|id| ships.iter().find(|s| s.id == *id))
+            .collect();
+
+        let leader = match squad.first() {
+            Some(leader) => (*leader).clone(),
+            None         => return vec!()
+        };
+
+        let target = self.get_target(&leader, ships, range);
+        match target {
+            Some(ref t) if self.target != Some(t.id) => {
+                // a new fight - pick how to go about it
+                self.tactic = if self.dice.gen_range(0, 2) == 0 { Tactic::Pincer } else { Tactic::StrafingRun };
+            },
+            _ => ()
+        }
+        self.target = target.as_ref().map(|t| t.id);
+
+        match target {
+            Some(target) => squad.iter().enumerate().map(|(i, ship)| {
+                (ship.id, Order::Attack(target.clone(), self.flank(i)))
+            }).collect(),
+            None => squad.iter().enumerate().map(|(i, ship)| {
+                if i == 0 {
+                    (ship.id, Order::Lead)
+                } else {
+                    (ship.id, Squadron::form(i, ship, &leader, &squad))
+                }
+            }).collect()
+        }
+    }
+
+    // the nearest enemy to the leader, sticking with the current one until it gets away
+    fn get_target(&self, leader: &ActorView, ships: &Vec<ActorView>, range: f32) -> Option<ActorView> {
+        let distance = |s: &ActorView| ((s.x - leader.x).powi(2) + (s.y - leader.y).powi(2)).sqrt();
+
+        match self.target.and_then(|id| ships.iter().find(|s| s.id == id)) {
+            Some(current) if distance(current) < range * DISENGAGE => return Some(current.clone()),
+            _ => ()
+        }
+
+        ships.iter()
+            .filter(|s| self.faction.is_hostile(&s.faction) && distance(s) < range)
+            .fold(None, |nearest: Option<&ActorView>, s| match nearest {
+                Some(n) if distance(n) <= distance(s) => Some(n),
+                _                                     => Some(s)
+            })
+            .cloned()
+    }
+
+    // the leader goes straight in, everyone else swings out either side
+    fn flank(&self, i: usize) -> f32 {
+        if i == 0 {
+            return 0.0;
+        }
+
+        let side = if i % 2 == 1 { 1.0 } else { -1.0 };
+        match self.tactic {
+            Tactic::Pincer      => side * PINCER_ANGLE,
+            Tactic::StrafingRun => side * STRAFE_ANGLE * ((i + 1) / 2) as f32
+        }
+    }
+
+    // boids - pulled towards our slot, matching the squadron's speed
+    // and heading, and kept off each other
+    fn form(i: usize, ship: &ActorView, leader: &ActorView, squad: &Vec<&ActorView>) -> Order {
+        let heading = if leader.vx * leader.vx + leader.vy * leader.vy > 1.0 {
+            leader.vx.atan2(leader.vy)
+        } else {
+            leader.rotation
+        };
+        let (sx, sy) = Squadron::slot(i, (leader.x, leader.y), heading);
+
+        let count = squad.len() as f32;
+        let (cx, cy) = (squad.iter().map(|s| s.x).sum::<f32>() / count, squad.iter().map(|s| s.y).sum::<f32>() / count);
+        let (avx, avy) = (squad.iter().map(|s| s.vx).sum::<f32>() / count, squad.iter().map(|s| s.vy).sum::<f32>() / count);
+
+        let mut dvx = (leader.vx - ship.vx) + (sx - ship.x) * SLOT_PULL + (avx - ship.vx) * ALIGNMENT + (cx - ship.x) * COHESION;
+        let mut dvy = (leader.vy - ship.vy) + (sy - ship.y) * SLOT_PULL + (avy - ship.vy) * ALIGNMENT + (cy - ship.y) * COHESION;
+
+        for other in squad.iter().filter(|s| s.id != ship.id) {
+            let (dx, dy) = (ship.x - other.x, ship.y - other.y);
+            let d = (dx * dx + dy * dy).sqrt().max(1.0);
+            if d < SEPARATION_RANGE {
+                dvx += dx / d * SEPARATION / d;
+                dvy += dy / d * SEPARATION / d;
+            }
+        }
+
+        Order::Form(dvx, dvy)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actor::Actor;
+    use spaceship::Spaceship;
+
+    fn ship(id: i32, x: i32, y: i32, faction: Faction) -> ActorView {
+        let mut s = Spaceship::new(id, x, y, 0.0);
+        s.set_faction(faction);
+        s.get_view()
+    }
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01
+    }
+
+    #[test]
+    fn slots_make_a_v_behind_the_leader(){
+        assert!(close(Squadron::slot(0, (100.0, 50.0), 0.0), (100.0, 50.0)));
+        // nose up - behind is down, odd slots to the right
+        assert!(close(Squadron::slot(1, (100.0, 50.0), 0.0), (100.0 + SLOT_SPACING, 50.0 - SLOT_SPACING)));
+        assert!(close(Squadron::slot(2, (100.0, 50.0), 0.0), (100.0 - SLOT_SPACING, 50.0 - SLOT_SPACING)));
+        assert!(close(Squadron::slot(3, (100.0, 50.0), 0.0), (100.0 + SLOT_SPACING * 2.0, 50.0 - SLOT_SPACING * 2.0)));
+
+        // nose right - behind is left, odd slots below
+        let right = 3.14159265359 / 2.0;
+        assert!(close(Squadron::slot(1, (0.0, 0.0), right), (-SLOT_SPACING, -SLOT_SPACING)));
+        assert!(close(Squadron::slot(2, (0.0, 0.0), right), (-SLOT_SPACING, SLOT_SPACING)));
+    }
+
+    #[test]
+    fn wingmen_flank_either_side_of_the_leader(){
+        let mut squadron = Squadron::new(vec!(1, 2, 3, 4, 5), Faction::Pirates, Dice::new(0));
+        squadron.tactic = Tactic::Pincer;
+        assert_eq!((0..5).map(|i| squadron.flank(i)).collect::<Vec<f32>>(),
+                   vec!(0.0, PINCER_ANGLE, -PINCER_ANGLE, PINCER_ANGLE, -PINCER_ANGLE));
+
+        squadron.tactic = Tactic::StrafingRun;
+        assert_eq!((0..5).map(|i| squadron.flank(i)).collect::<Vec<f32>>(),
+                   vec!(0.0, STRAFE_ANGLE, -STRAFE_ANGLE, STRAFE_ANGLE * 2.0, -STRAFE_ANGLE * 2.0));
+    }
+
+    #[test]
+    fn everyone_goes_for_the_enemy_the_leader_sees(){
+        let mut squadron = Squadron::new(vec!(1, 2, 3), Faction::Pirates, Dice::new(0));
+        let ships = vec!(ship(1, 0, 0, Faction::Pirates), ship(2, 200, -200, Faction::Pirates),
+                         ship(3, -200, -200, Faction::Pirates), ship(9, 0, 800, Faction::Police));
+
+        let orders = squadron.update(&ships, 1000.0);
+        assert_eq!(orders.len(), 3);
+        for (i, &(id, ref order)) in orders.iter().enumerate() {
+            assert_eq!(id, i as i32 + 1);
+            match order {
+                &Order::Attack(ref target, flank) => {
+                    assert_eq!(target.id, 9);
+                    assert_eq!(flank, squadron.flank(i));
+                },
+                _ => panic!("{:?}", order)
+            }
+        }
+    }
+
+    #[test]
+    fn the_next_in_line_takes_over_when_the_leader_goes_down(){
+        let mut squadron = Squadron::new(vec!(1, 2, 3), Faction::Pirates, Dice::new(0));
+        let mut ships = vec!(ship(1, 0, 0, Faction::Pirates), ship(2, 200, -200, Faction::Pirates), ship(3, -200, -200, Faction::Pirates));
+        assert_eq!(squadron.update(&ships, 1000.0)[0], (1, Order::Lead));
+
+        ships.remove(0);
+        let orders = squadron.update(&ships, 1000.0);
+        assert_eq!(squadron.members, vec!(2, 3));
+        assert_eq!(orders[0], (2, Order::Lead));
+        match orders[1] {
+            (3, Order::Form(_, _)) => (),
+            ref other              => panic!("{:?}", other)
+        }
+
+        ships.clear();
+        assert!(squadron.update(&ships, 1000.0).is_empty());
+        assert!(squadron.members.is_empty());
+    }
+}
@@ -1,10 +1,10 @@
 use actor::Actor;
 use actor::ActorView;
-use rand;
 use rand::Rng;
 use actor;
 use messages::PlayerInstructions;
 use messages::GameInstructions;
+use faction::Faction;
 
 static PI : f32 = 3.14159265359;
 
@@ -22,9 +22,9 @@ pub struct Token{
 }
 
 impl Token{
-    pub fn new(id: i32, x: i32, y: i32) -> Token {
+    pub fn new<R: Rng>(id: i32, x: i32, y: i32, rng: &mut R) -> Token {
 
-        let r = rand::thread_rng().gen_range(-5.0f32, 5.0);
+        let r = rng.gen_range(-5.0f32, 5.0);
 
         let shape = vec!(
             -0.02,  0.05,
@@ -69,8 +69,11 @@ impl Actor for Token{
         ActorView {
             id: self.id,
             parent: 0,
+            faction: Faction::Unaligned,
             x: self.x,
             y: self.y,
+            vx: 0.0,
+            vy: 0.0,
             width: 100.0,
             height: 100.0,
             rotation: (self.rotation * PI) / 180.0,
@@ -80,13 +83,16 @@ impl Actor for Token{
             show_secondary: false,
             secondary_shape: None,
             secondary_color: None,
-            meter: 0.0
+            meter: 0.0,
+            cooldown: 0.0,
+            damage: 0.0,
+            hull: 1.0
         }
     }
 
     fn execute(&mut self, message: &PlayerInstructions, _:&mut Vec<(GameInstructions, ActorView)>){
         match message {
-            &PlayerInstructions::Collide                       => {
+            &PlayerInstructions::Collide(_)                     => {
                                             //self.is_alive = false;
                                             //output_messages.push(("explode", self.get_view().clone()));
                                         },